@@ -0,0 +1,428 @@
+use std::collections::VecDeque;
+
+use tcod::colors::*;
+
+use crate::constants::*;
+use crate::controls::PlayerAction;
+use crate::objects::{ai::AI, game::Game, hunger::Hunger, object::Object, player::*, Tcod};
+use crate::render::{messages::Category, *};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Activity {
+    pub goal: Goal,
+    // hit points after the last step, to notice being hurt
+    hp: i32,
+    // items already in view, so only new ones interrupt
+    seen_items: Vec<(i32, i32)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Goal {
+    Explore,
+    Travel {
+        x: i32,
+        y: i32,
+    },
+    // `open_around` counts the open tiles around the previous position, to notice side openings
+    Run {
+        dx: i32,
+        dy: i32,
+        open_around: Option<usize>,
+    },
+    Rest {
+        turns: u32,
+        start_hp: i32,
+    },
+}
+
+fn hostile_in_view(tcod: &Tcod, objects: &[Object]) -> Option<usize> {
+    objects.iter().position(|object| {
+        object.alive
+            && object.fighter.is_some()
+            && object.ai.is_some()
+            && object.ai != Some(AI::Shopkeeper)
+            && tcod.fov.is_in_fov(object.x, object.y)
+    })
+}
+
+fn items_in_view(tcod: &Tcod, objects: &[Object]) -> Vec<(i32, i32)> {
+    objects
+        .iter()
+        .filter(|object| object.item.is_some() && tcod.fov.is_in_fov(object.x, object.y))
+        .map(|object| object.pos())
+        .collect()
+}
+
+pub fn start_activity(goal: Goal, tcod: &Tcod, game: &mut Game, objects: &[Object]) {
+    if let Some(monster) = hostile_in_view(tcod, objects) {
+        game.messages.add(
+            format!("Not with the {} in view!", objects[monster].name),
+            RED,
+            Category::System,
+        );
+        return;
+    }
+    game.activity = Some(Activity {
+        goal,
+        hp: objects[PLAYER].fighter.map_or(0, |f| f.hp),
+        seen_items: items_in_view(tcod, objects),
+    });
+}
+
+pub fn stop_activity(game: &mut Game) {
+    if let Some(Activity {
+        goal: Goal::Rest { turns, start_hp },
+        hp,
+        ..
+    }) = game.activity.take()
+    {
+        if turns == 0 {
+            return;
+        }
+        // one line for the whole rest instead of a message per turn
+        game.messages.add(
+            format!("You rest for {} turns and recover {} hit points.", turns, hp - start_hp),
+            LIGHT_GREY,
+            Category::System,
+        );
+    }
+}
+
+fn interruption(activity: &Activity, tcod: &Tcod, game: &Game, objects: &[Object]) -> Option<String> {
+    if let Some(monster) = hostile_in_view(tcod, objects) {
+        return Some(format!("You see the {}.", objects[monster].name));
+    }
+    if objects[PLAYER].fighter.map_or(0, |f| f.hp) < activity.hp {
+        return Some("You are hurt!".into());
+    }
+    if let Goal::Rest { .. } = activity.goal {
+        if Hunger::from_satiation(game.satiation) >= Hunger::Hungry {
+            return Some("You are too hungry to rest.".into());
+        }
+    }
+    let watch_items = match activity.goal {
+        Goal::Explore => !AUTO_PICKUP,
+        Goal::Run { .. } => true,
+        Goal::Travel { .. } | Goal::Rest { .. } => false,
+    };
+    if watch_items {
+        let new_item = objects.iter().find(|object| {
+            object.item.is_some()
+                && tcod.fov.is_in_fov(object.x, object.y)
+                && !activity.seen_items.contains(&object.pos())
+        });
+        if let Some(item) = new_item {
+            return Some(format!("You see {}.", item.description(game)));
+        }
+    }
+    None
+}
+
+pub fn continue_activity(tcod: &mut Tcod, game: &mut Game, objects: &mut Vec<Object>) -> PlayerAction {
+    let mut activity = match game.activity.clone() {
+        Some(activity) => activity,
+        None => return PlayerAction::DidntTakeTurn,
+    };
+    if let Some(reason) = interruption(&activity, tcod, game, objects) {
+        game.messages.add(reason, LIGHT_GREY, Category::System);
+        stop_activity(game);
+        return PlayerAction::DidntTakeTurn;
+    }
+
+    match activity.goal {
+        Goal::Explore => {
+            if AUTO_PICKUP {
+                let item_id = objects.iter().position(|object| {
+                    object.pos() == objects[PLAYER].pos() && object.item.is_some() && object.price.is_none()
+                });
+                if let Some(item_id) = item_id {
+                    if pick_item_up(item_id, game, objects) {
+                        return PlayerAction::TookTurn;
+                    }
+                }
+            }
+            let step = first_step(game, objects, |x, y| {
+                let unexplored_neighbour =
+                    neighbours(x, y).any(|(nx, ny)| !game.map[nx as usize][ny as usize].explored);
+                let wanted_item = AUTO_PICKUP
+                    && objects
+                        .iter()
+                        .any(|object| object.pos() == (x, y) && object.item.is_some() && object.price.is_none());
+                unexplored_neighbour || wanted_item
+            });
+            match step {
+                Some((dx, dy)) => player_move_or_attack(dx, dy, tcod, game, objects),
+                None => {
+                    game.messages
+                        .add("There is nothing left to explore.", LIGHT_GREY, Category::System);
+                    stop_activity(game);
+                    return PlayerAction::DidntTakeTurn;
+                }
+            }
+        }
+        Goal::Travel { x, y } => {
+            if objects[PLAYER].pos() == (x, y) {
+                stop_activity(game);
+                return PlayerAction::DidntTakeTurn;
+            }
+            match first_step(game, objects, |tile_x, tile_y| (tile_x, tile_y) == (x, y)) {
+                Some((dx, dy)) => player_move_or_attack(dx, dy, tcod, game, objects),
+                None => {
+                    game.messages
+                        .add("You can't find a way there.", LIGHT_GREY, Category::System);
+                    stop_activity(game);
+                    return PlayerAction::DidntTakeTurn;
+                }
+            }
+        }
+        Goal::Run { dx, dy, open_around } => {
+            let (x, y) = objects[PLAYER].pos();
+            let around = neighbours(x, y).filter(|&(nx, ny)| is_open(nx, ny, &game.map)).count();
+            let heading = match open_around {
+                None => Some((dx, dy)),
+                Some(_) if is_corridor(x, y, &game.map) => {
+                    // follow the corridor around corners, stopping where it opens up or branches
+                    let back = (x - dx, y - dy);
+                    let exits: Vec<_> = cardinal_neighbours(x, y)
+                        .filter(|&(nx, ny)| (nx, ny) != back && is_open(nx, ny, &game.map))
+                        .collect();
+                    match exits[..] {
+                        [(nx, ny)] if is_corridor(nx, ny, &game.map) => Some((nx - x, ny - y)),
+                        _ => None,
+                    }
+                }
+                // across a room, keep going straight until the walls around change
+                Some(previous) if previous == around => Some((dx, dy)),
+                Some(_) => None,
+            };
+            match heading {
+                Some((dx, dy)) if !is_blocked(x + dx, y + dy, &game.map, objects) => {
+                    player_move_or_attack(dx, dy, tcod, game, objects);
+                    activity.goal = Goal::Run {
+                        dx,
+                        dy,
+                        open_around: Some(around),
+                    };
+                    let on_item = objects
+                        .iter()
+                        .any(|object| object.pos() == objects[PLAYER].pos() && object.item.is_some());
+                    if on_item {
+                        stop_activity(game);
+                        return PlayerAction::TookTurn;
+                    }
+                }
+                _ => {
+                    stop_activity(game);
+                    return PlayerAction::DidntTakeTurn;
+                }
+            }
+        }
+        Goal::Rest { turns, start_hp } => {
+            let hp = objects[PLAYER].fighter.map_or(0, |f| f.hp);
+            if hp >= objects[PLAYER].max_hp(game) {
+                game.messages
+                    .add("You feel fully rested.", LIGHT_GREY, Category::System);
+                if let Some(activity) = game.activity.as_mut() {
+                    activity.hp = hp;
+                }
+                stop_activity(game);
+                return PlayerAction::DidntTakeTurn;
+            }
+            activity.goal = Goal::Rest {
+                turns: turns + 1,
+                start_hp,
+            };
+        }
+    }
+
+    activity.hp = objects[PLAYER].fighter.map_or(0, |f| f.hp);
+    if game.activity.is_some() {
+        game.activity = Some(activity);
+    }
+    PlayerAction::TookTurn
+}
+
+fn neighbours(x: i32, y: i32) -> impl Iterator<Item = (i32, i32)> {
+    (-1..=1)
+        .flat_map(move |dx| (-1..=1).map(move |dy| (x + dx, y + dy)))
+        .filter(move |&(nx, ny)| (nx, ny) != (x, y) && nx >= 0 && ny >= 0 && nx < MAP_WIDTH && ny < MAP_HEIGHT)
+}
+
+fn cardinal_neighbours(x: i32, y: i32) -> impl Iterator<Item = (i32, i32)> {
+    neighbours(x, y).filter(move |&(nx, ny)| nx == x || ny == y)
+}
+
+fn is_open(x: i32, y: i32, map: &Map) -> bool {
+    !map[x as usize][y as usize].blocked
+}
+
+// at most two ways out along the axes, like the tunnels between rooms
+fn is_corridor(x: i32, y: i32, map: &Map) -> bool {
+    cardinal_neighbours(x, y)
+        .filter(|&(nx, ny)| is_open(nx, ny, map))
+        .count()
+        <= 2
+}
+
+// breadth-first over explored, walkable tiles; returns the direction of the first step
+pub fn first_step<F: Fn(i32, i32) -> bool>(game: &Game, objects: &[Object], goal: F) -> Option<(i32, i32)> {
+    let start = objects[PLAYER].pos();
+    let mut came_from = vec![vec![None; MAP_HEIGHT as usize]; MAP_WIDTH as usize];
+    let mut queue = VecDeque::new();
+    came_from[start.0 as usize][start.1 as usize] = Some(start);
+    queue.push_back(start);
+
+    while let Some((x, y)) = queue.pop_front() {
+        if (x, y) != start && goal(x, y) {
+            // walk back to the tile next to the player
+            let mut step = (x, y);
+            while let Some(previous) = came_from[step.0 as usize][step.1 as usize].filter(|&previous| previous != start)
+            {
+                step = previous;
+            }
+            return Some((step.0 - start.0, step.1 - start.1));
+        }
+        for (nx, ny) in neighbours(x, y) {
+            let tile = &game.map[nx as usize][ny as usize];
+            if came_from[nx as usize][ny as usize].is_none() && tile.explored && !is_blocked(nx, ny, &game.map, objects)
+            {
+                came_from[nx as usize][ny as usize] = Some((x, y));
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+    None
+}
+
+pub fn travel_to_click(tcod: &Tcod, game: &mut Game, objects: &[Object]) {
+    let (x, y) = (tcod.mouse.cx as i32, tcod.mouse.cy as i32);
+    if x < MAP_WIDTH && y < MAP_HEIGHT && game.map[x as usize][y as usize].explored {
+        start_activity(Goal::Travel { x, y }, tcod, game, objects);
+    }
+}
+
+pub fn travel_to_stairs(tcod: &Tcod, game: &mut Game, objects: &[Object]) {
+    let stairs = objects
+        .iter()
+        .find(|object| object.name == "stairs" && game.map[object.x as usize][object.y as usize].explored);
+    match stairs {
+        Some(stairs) => {
+            let (x, y) = stairs.pos();
+            start_activity(Goal::Travel { x, y }, tcod, game, objects);
+        }
+        None => game
+            .messages
+            .add("You haven't found the stairs yet.", WHITE, Category::System),
+    }
+}
+
+pub fn start_run(dx: i32, dy: i32, tcod: &Tcod, game: &mut Game, objects: &[Object]) {
+    let goal = Goal::Run {
+        dx,
+        dy,
+        open_around: None,
+    };
+    start_activity(goal, tcod, game, objects);
+}
+
+pub fn rest(tcod: &Tcod, game: &mut Game, objects: &[Object]) {
+    let hp = objects[PLAYER].fighter.map_or(0, |f| f.hp);
+    if hp >= objects[PLAYER].max_hp(game) {
+        game.messages
+            .add("You are already at full health.", LIGHT_GREY, Category::System);
+        return;
+    }
+    if Hunger::from_satiation(game.satiation) >= Hunger::Hungry {
+        game.messages
+            .add("You are too hungry to rest.", LIGHT_GREY, Category::System);
+        return;
+    }
+    let goal = Goal::Rest { turns: 0, start_hp: hp };
+    start_activity(goal, tcod, game, objects);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::class::Class;
+    use crate::render::messages::Messages;
+
+    fn open_game() -> Game {
+        let mut tile = Tile::empty();
+        tile.explored = true;
+        Game {
+            map: vec![vec![tile; MAP_HEIGHT as usize]; MAP_WIDTH as usize],
+            messages: Messages::new(),
+            inventory: vec![],
+            dungeon_level: 1,
+            class: Class::Warrior,
+            dropped: vec![],
+            appearances: vec![],
+            identified: vec![],
+            satiation: START_SATIATION,
+            gold: 0,
+            mana: 0,
+            max_mana: 0,
+            spells: vec![],
+            perks: vec![],
+            perk_points: 0,
+            activity: None,
+        }
+    }
+
+    // a wall down column 3 with a single gap at the given row
+    fn wall_with_gap(game: &mut Game, gap: i32) {
+        for y in 0..MAP_HEIGHT {
+            if y != gap {
+                game.map[3][y as usize] = Tile::wall();
+            }
+        }
+    }
+
+    fn player_at(x: i32, y: i32) -> Vec<Object> {
+        vec![Object::new(x, y, '@', "player", WHITE, true)]
+    }
+
+    #[test]
+    fn steps_straight_towards_an_open_goal() {
+        let game = open_game();
+        let objects = player_at(1, 1);
+        // diagonal steps cost the same, so any step east is on a shortest path
+        let (dx, _) = first_step(&game, &objects, |x, y| (x, y) == (5, 1)).unwrap();
+        assert_eq!(dx, 1);
+    }
+
+    #[test]
+    fn steps_around_walls() {
+        let mut game = open_game();
+        wall_with_gap(&mut game, 6);
+        let objects = player_at(1, 1);
+        let (_, dy) = first_step(&game, &objects, |x, y| (x, y) == (5, 1)).unwrap();
+        assert_eq!(dy, 1);
+    }
+
+    #[test]
+    fn never_steps_onto_unexplored_tiles() {
+        let mut game = open_game();
+        wall_with_gap(&mut game, 6);
+        game.map[3][6].explored = false;
+        let objects = player_at(1, 1);
+        assert_eq!(first_step(&game, &objects, |x, y| (x, y) == (5, 1)), None);
+    }
+
+    #[test]
+    fn blocking_objects_close_the_way() {
+        let mut game = open_game();
+        wall_with_gap(&mut game, 6);
+        let mut objects = player_at(1, 1);
+        objects.push(Object::new(3, 6, 'o', "orc", WHITE, true));
+        assert_eq!(first_step(&game, &objects, |x, y| (x, y) == (5, 1)), None);
+    }
+
+    #[test]
+    fn the_players_own_tile_is_never_the_goal() {
+        let game = open_game();
+        let objects = player_at(1, 1);
+        assert_eq!(first_step(&game, &objects, |x, y| (x, y) == (1, 1)), None);
+    }
+}
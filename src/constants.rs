@@ -21,6 +21,8 @@ pub const COLOR_LIGHT_GROUND: Color = Color { r: 200, g: 180, b: 50 };
 pub const MSG_X: i32 = BAR_WIDTH + 2;
 pub const MSG_WIDTH: i32 = SCREEN_WIDTH - BAR_WIDTH - 2;
 pub const MSG_HEIGHT: usize = PANEL_HEIGHT as usize - 1;
+// older messages are dropped, so saves don't keep growing
+pub const MAX_MESSAGES: usize = 500;
 
 //parameters for dungeon generator
 pub const ROOM_MAX_SIZE: i32 = 10;
@@ -44,9 +46,100 @@ pub const CONFUSE_RANGE: i32 = 8;
 pub const CONFUSE_NUM_TURNS: i32 = 10;
 pub const FIREBALL_RADIUS: i32 = 3;
 pub const FIREBALL_DAMAGE: i32 = 25;
+pub const RECHARGE_AMOUNT: u32 = 3;
+pub const FIRE_POTION_DAMAGE: i32 = 15;
+
+// Throwing
+pub const THROW_RANGE: i32 = 8;
+pub const THROWN_DAGGER_BONUS: i32 = 3;
+pub const SHATTER_RADIUS: i32 = 1;
+
+// Ranged weapons
+pub const LONG_RANGE_FACTOR: i32 = 2;
+pub const AMMO_BREAK_CHANCE: u32 = 25;
+
+// Identification
+pub const POTION_APPEARANCES: &[(&str, Color)] = &[
+    ("murky", DARK_SEPIA),
+    ("bubbling", LIGHT_GREEN),
+    ("smoky", GREY),
+    ("fizzy", LIGHT_CYAN),
+    ("golden", GOLD),
+    ("violet", VIOLET),
+    ("milky", LIGHTEST_GREY),
+    ("glowing", LIGHT_YELLOW),
+];
+pub const SCROLL_LABELS: &[&str] = &[
+    "XYZZY",
+    "FOOBIE BLETCH",
+    "ZELGO MER",
+    "ELBIB YLOH",
+    "KIRJE",
+    "VELOX NEB",
+    "THARR",
+    "DAIYEN FOOELS",
+    "PRATYAVAYAH",
+    "ANDOVA BEGARIN",
+];
+pub const UNIDENTIFIED_SCROLL_COLOR: Color = LIGHTEST_SEPIA;
+// prompts of unknown scrolls, which mustn't say what they do
+pub const UNKNOWN_TARGET_PROMPT: &str = "Choose a target with the cursor or mouse, Enter to confirm, Escape to cancel.";
+pub const UNKNOWN_ITEM_PROMPT: &str = "Choose an item, or any other key to cancel.\n";
+
+// Hunger
+pub const MAX_SATIATION: i32 = 2000;
+pub const START_SATIATION: i32 = 1200;
+pub const HUNGRY_SATIATION: i32 = 400;
+pub const WEAK_SATIATION: i32 = 150;
+pub const FAINTING_SATIATION: i32 = 50;
+pub const WEAK_POWER_PENALTY: i32 = 1;
+pub const FAINT_CHANCE: u32 = 10;
+pub const FAINT_TURNS: u32 = 3;
+pub const STARVATION_DAMAGE: i32 = 1;
+pub const POISONOUS_FOOD_DAMAGE: i32 = 10;
+pub const RATION_NUTRITION: i32 = 800;
+pub const APPLE_NUTRITION: i32 = 150;
+
+// Enchantments
+pub const CURSED_CHANCE: u32 = 15;
+pub const ENCHANTED_CHANCE: u32 = 15;
+pub const MAX_ENCHANTMENT: i32 = 3;
+
+// Containers
+pub const BAG_CAPACITY: usize = 20;
+pub const CHEST_CHANCE: u32 = 20;
+pub const CHEST_LOCKED_CHANCE: u32 = 30;
+pub const CHEST_TRAPPED_CHANCE: u32 = 20;
+pub const MAX_CHEST_ITEMS: u32 = 3;
+pub const FORCE_LOCK_CHANCE: u32 = 40;
+pub const LOCKPICKING_CHANCE: u32 = 90;
+pub const CHEST_TRAP_DAMAGE: i32 = 8;
+
+// Shops
+pub const SHOP_CHANCE: u32 = 15;
+pub const SHOP_STOCK: u32 = 8;
+
+// Natural healing: chance out of HP_REGEN_INTERVAL is the player level plus max HP / HP_PER_REGEN_CHANCE
+pub const HP_REGEN_INTERVAL: i32 = 40;
+pub const HP_PER_REGEN_CHANCE: i32 = 20;
+
+// Magic
+pub const MANA_REGEN_INTERVAL: u32 = 5;
+
+// Perks
+pub const DODGE_CHANCE: u32 = 20;
+pub const SPRINT_CHANCE: u32 = 25;
+pub const REGENERATION_INTERVAL: u32 = 4;
+pub const PERK_SCREEN_WIDTH: i32 = 70;
+
+// Auto-explore
+pub const AUTO_PICKUP: bool = false;
 
 // Level ups
 pub const LEVEL_UP_BASE: i32 = 200;
 pub const LEVEL_UP_FACTOR: i32 = 150;
 pub const LEVEL_SCREEN_WIDTH: i32 = 40;
-pub const CHARACTER_SCREEN_WIDTH: i32 = 30;
+pub const CHARACTER_SCREEN_WIDTH: i32 = 45;
+pub const LOOK_WIDTH: i32 = 30;
+pub const HELP_WIDTH: i32 = 50;
+pub const CLASS_SCREEN_WIDTH: i32 = 60;
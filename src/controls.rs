@@ -1,8 +1,21 @@
+use tcod::colors::*;
 use tcod::input::Key;
 
+use crate::activity::*;
 use crate::constants::*;
-use crate::objects::{game::*, object::*, player::*, Tcod};
-use crate::render::{menus::inventory_menu, *};
+use crate::keymap::Action;
+use crate::objects::{
+    equipment::paper_doll,
+    game::*,
+    hunger::eat_something,
+    object::*,
+    perk::{choose_perk, perk_list},
+    player::*,
+    projectile::*,
+    spell::cast_spell,
+    Tcod,
+};
+use crate::render::{history::message_log, look::look, menus::*, messages::Category, *};
 use crate::ui::msgbox;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -14,57 +27,67 @@ pub enum PlayerAction {
 
 pub fn handle_keys(tcod: &mut Tcod, game: &mut Game, objects: &mut Vec<Object>) -> PlayerAction {
     use tcod::input::KeyCode::*;
+    use Action::*;
     use PlayerAction::*;
     let player_alive = objects[PLAYER].alive;
-    match (tcod.key, tcod.key.text(), player_alive) {
-        (
-            Key {
-                code: Enter, alt: true, ..
-            },
-            _,
-            _,
-        ) => {
+    if tcod.mouse.lbutton_pressed && player_alive {
+        travel_to_click(tcod, game, objects);
+        return DidntTakeTurn;
+    }
+    match tcod.key {
+        Key {
+            code: Enter, alt: true, ..
+        } => {
             let fullscreen = tcod.root.is_fullscreen();
             tcod.root.set_fullscreen(!fullscreen);
-            DidntTakeTurn
-        }
-        (Key { code: Escape, .. }, _, _) => Exit,
-        (Key { code: Up, .. }, _, true) => {
-            player_move_or_attack(0, -1, game, objects);
-            TookTurn
-        }
-        (Key { code: Down, .. }, _, true) => {
-            player_move_or_attack(0, 1, game, objects);
-            TookTurn
+            return DidntTakeTurn;
         }
-        (Key { code: Left, .. }, _, true) => {
-            player_move_or_attack(-1, 0, game, objects);
-            TookTurn
-        }
-        (Key { code: Right, .. }, _, true) => {
-            player_move_or_attack(1, 0, game, objects);
-            TookTurn
+        Key { code: Escape, .. } => return Exit,
+        _ => {}
+    }
+    let (action, run) = match tcod.keymap.action(tcod.key) {
+        Some(found) => found,
+        None => return DidntTakeTurn,
+    };
+    match (action, player_alive) {
+        (Help, _) => {
+            show_help(tcod);
+            DidntTakeTurn
         }
-        (Key { code: Home, .. }, _, true) | (Key { code: NumPad7, .. }, _, true) => {
-            player_move_or_attack(-1, -1, game, objects);
-            TookTurn
+        (_, false) => DidntTakeTurn,
+        (Look, true) => {
+            look(tcod, game, objects);
+            DidntTakeTurn
         }
-        (Key { code: PageUp, .. }, _, true) | (Key { code: NumPad9, .. }, _, true) => {
-            player_move_or_attack(1, -1, game, objects);
-            TookTurn
+        (MessageLog, _) => {
+            message_log(tcod, game);
+            DidntTakeTurn
         }
-        (Key { code: End, .. }, _, true) | (Key { code: NumPad1, .. }, _, true) => {
-            player_move_or_attack(-1, 1, game, objects);
-            TookTurn
+        (Rest, true) => {
+            rest(tcod, game, objects);
+            DidntTakeTurn
         }
-        (Key { code: PageDown, .. }, _, true) | (Key { code: NumPad3, .. }, _, true) => {
-            player_move_or_attack(1, 1, game, objects);
-            TookTurn
+        (North, true)
+        | (South, true)
+        | (West, true)
+        | (East, true)
+        | (NorthWest, true)
+        | (NorthEast, true)
+        | (SouthWest, true)
+        | (SouthEast, true) => {
+            let (dx, dy) = action.direction().unwrap();
+            if run {
+                start_run(dx, dy, tcod, game, objects);
+                DidntTakeTurn
+            } else {
+                player_move_or_attack(dx, dy, tcod, game, objects);
+                TookTurn
+            }
         }
-        (Key { code: NumPad5, .. }, _, true) => {
+        (Wait, true) => {
             TookTurn // do nothing, i.e. wait for the monster to come to you
         }
-        (Key { code: Text, .. }, "g", true) => {
+        (PickUp, true) => {
             let item_id = objects
                 .iter()
                 .position(|object| object.pos() == objects[PLAYER].pos() && object.item.is_some());
@@ -73,9 +96,9 @@ pub fn handle_keys(tcod: &mut Tcod, game: &mut Game, objects: &mut Vec<Object>)
             }
             TookTurn
         }
-        (Key { code: Text, .. }, "i", true) => {
+        (Inventory, true) => {
             let inventory_index = inventory_menu(
-                &game.inventory,
+                game,
                 "Press the key next to an item to use it, or any other to cancel.\n",
                 &mut tcod.root,
             );
@@ -84,19 +107,84 @@ pub fn handle_keys(tcod: &mut Tcod, game: &mut Game, objects: &mut Vec<Object>)
             }
             TookTurn
         }
-        (Key { code: Text, .. }, "d", true) => {
-            // show the inventory; if an item is selected, drop it
+        (Drop, true) => {
+            // show the inventory; drop whatever is marked
+            let mut chosen = inventory_multi_menu(
+                game,
+                "Mark items to drop with their key, then press Enter. Any other key cancels.\n",
+                &mut tcod.root,
+            );
+            if let [inventory_index] = chosen[..] {
+                // a single stack may be split
+                let quantity = game.inventory[inventory_index].quantity;
+                let count = if quantity > 1 {
+                    number_prompt("Drop how many?", quantity, &mut tcod.root)
+                } else {
+                    Some(1)
+                };
+                if let Some(count) = count {
+                    drop_item(inventory_index, count, game, objects);
+                }
+            } else {
+                // later indices first, so the earlier ones stay valid
+                chosen.sort_unstable_by(|a, b| b.cmp(a));
+                for inventory_index in chosen {
+                    let quantity = game.inventory[inventory_index].quantity;
+                    drop_item(inventory_index, quantity, game, objects);
+                }
+            }
+            DidntTakeTurn
+        }
+        (Throw, true) => {
             let inventory_index = inventory_menu(
-                &game.inventory,
-                "Press the key next to an item to drop it, or any other to cancel.\n'",
+                game,
+                "Press the key next to an item to throw it, or any other to cancel.\n",
                 &mut tcod.root,
             );
-            if let Some(inventory_index) = inventory_index {
-                drop_item(inventory_index, game, objects);
+            match inventory_index {
+                Some(inventory_index) if throw_item(inventory_index, tcod, game, objects) => TookTurn,
+                _ => DidntTakeTurn,
+            }
+        }
+        (Eat, true) => {
+            if eat_something(tcod, game, objects) {
+                TookTurn
+            } else {
+                DidntTakeTurn
+            }
+        }
+        (Fire, true) => {
+            if fire_weapon(tcod, game, objects) {
+                TookTurn
+            } else {
+                DidntTakeTurn
+            }
+        }
+        (Cast, true) => {
+            if cast_spell(tcod, game, objects) {
+                TookTurn
+            } else {
+                DidntTakeTurn
+            }
+        }
+        (Explore, true) => {
+            start_activity(Goal::Explore, tcod, game, objects);
+            DidntTakeTurn
+        }
+        (TravelToStairs, true) => {
+            travel_to_stairs(tcod, game, objects);
+            DidntTakeTurn
+        }
+        (Perks, true) => {
+            if game.perk_points > 0 {
+                choose_perk(tcod, game);
+            } else {
+                game.messages
+                    .add("You have no perk points to spend.", WHITE, Category::System);
             }
             DidntTakeTurn
         }
-        (Key { code: Text, .. }, "<", true) => {
+        (Descend, true) => {
             let player_on_stairs = objects
                 .iter()
                 .any(|object| object.pos() == objects[PLAYER].pos() && object.name == "stairs");
@@ -105,7 +193,7 @@ pub fn handle_keys(tcod: &mut Tcod, game: &mut Game, objects: &mut Vec<Object>)
             }
             DidntTakeTurn
         }
-        (Key { code: Text, .. }, "c", true) => {
+        (Character, true) => {
             // show character information
             let player = &objects[PLAYER];
             let level = player.level;
@@ -114,25 +202,54 @@ pub fn handle_keys(tcod: &mut Tcod, game: &mut Game, objects: &mut Vec<Object>)
                 let msg = format!(
                     "Character information
 
+        Class: {}
         Level: {}
         Experience: {}
         Experience to level up: {}
 
         Maximum HP: {}
+        Maximum MP: {}
         Attack: {}
-        Defense: {}",
+        Defense: {}
+
+Perks: {}
+
+Equipment:
+{}",
+                    game.class,
                     level,
                     fighter.xp,
                     level_up_xp,
                     player.max_hp(game),
+                    game.max_mana,
                     player.power(game),
-                    player.defense(game)
+                    player.defense(game),
+                    perk_list(game),
+                    paper_doll(&game.inventory)
                 );
                 msgbox(&msg, CHARACTER_SCREEN_WIDTH, &mut tcod.root);
             }
 
             DidntTakeTurn
         }
-        _ => DidntTakeTurn,
     }
 }
+
+fn show_help(tcod: &mut Tcod) {
+    let mut lines: Vec<String> = Action::ALL
+        .iter()
+        .map(|&action| {
+            format!(
+                "{:>12}  {}",
+                tcod.keymap.keys_for(action).join(" "),
+                action.description()
+            )
+        })
+        .collect();
+    lines.push(format!("{:>12}  {}", "Shift+move", "run"));
+    lines.push(format!("{:>12}  {}", "Left-click", "travel there"));
+    lines.push(format!("{:>12}  {}", "Alt+Enter", "toggle fullscreen"));
+    lines.push(format!("{:>12}  {}", "Escape", "save and quit"));
+    let text = format!("Keys\n\n{}", lines.join("\n"));
+    msgbox(&text, HELP_WIDTH, &mut tcod.root);
+}
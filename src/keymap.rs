@@ -0,0 +1,468 @@
+use std::collections::HashMap;
+use std::fs;
+
+use serde::Deserialize;
+use tcod::input::{Key, KeyCode};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum Action {
+    North,
+    South,
+    West,
+    East,
+    NorthWest,
+    NorthEast,
+    SouthWest,
+    SouthEast,
+    Wait,
+    PickUp,
+    Inventory,
+    Drop,
+    Throw,
+    Eat,
+    Fire,
+    Cast,
+    Explore,
+    TravelToStairs,
+    Perks,
+    Descend,
+    Character,
+    Look,
+    Rest,
+    MessageLog,
+    Help,
+}
+
+impl Action {
+    // in help screen order
+    pub const ALL: [Action; 25] = [
+        Action::North,
+        Action::South,
+        Action::West,
+        Action::East,
+        Action::NorthWest,
+        Action::NorthEast,
+        Action::SouthWest,
+        Action::SouthEast,
+        Action::Wait,
+        Action::PickUp,
+        Action::Inventory,
+        Action::Drop,
+        Action::Throw,
+        Action::Eat,
+        Action::Fire,
+        Action::Cast,
+        Action::Explore,
+        Action::TravelToStairs,
+        Action::Perks,
+        Action::Descend,
+        Action::Character,
+        Action::Look,
+        Action::Rest,
+        Action::MessageLog,
+        Action::Help,
+    ];
+
+    pub fn direction(self) -> Option<(i32, i32)> {
+        match self {
+            Action::North => Some((0, -1)),
+            Action::South => Some((0, 1)),
+            Action::West => Some((-1, 0)),
+            Action::East => Some((1, 0)),
+            Action::NorthWest => Some((-1, -1)),
+            Action::NorthEast => Some((1, -1)),
+            Action::SouthWest => Some((-1, 1)),
+            Action::SouthEast => Some((1, 1)),
+            _ => None,
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::North => "move north",
+            Action::South => "move south",
+            Action::West => "move west",
+            Action::East => "move east",
+            Action::NorthWest => "move north-west",
+            Action::NorthEast => "move north-east",
+            Action::SouthWest => "move south-west",
+            Action::SouthEast => "move south-east",
+            Action::Wait => "wait a turn",
+            Action::PickUp => "pick up",
+            Action::Inventory => "use an item",
+            Action::Drop => "drop an item",
+            Action::Throw => "throw an item",
+            Action::Eat => "eat",
+            Action::Fire => "fire your launcher",
+            Action::Cast => "cast a spell",
+            Action::Explore => "explore",
+            Action::TravelToStairs => "travel to the stairs",
+            Action::Perks => "choose a perk",
+            Action::Descend => "go down the stairs",
+            Action::Character => "character screen",
+            Action::Look => "look around",
+            Action::Rest => "rest until healed",
+            Action::MessageLog => "message log",
+            Action::Help => "this help",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub enum Preset {
+    Numpad,
+    Vi,
+    Wasd,
+}
+
+#[derive(Debug, Deserialize)]
+struct KeymapFile {
+    preset: Preset,
+    #[serde(default)]
+    bindings: HashMap<String, Action>,
+}
+
+const NAMED_KEYS: [(KeyCode, &str); 17] = [
+    (KeyCode::Up, "Up"),
+    (KeyCode::Down, "Down"),
+    (KeyCode::Left, "Left"),
+    (KeyCode::Right, "Right"),
+    (KeyCode::Home, "Home"),
+    (KeyCode::End, "End"),
+    (KeyCode::PageUp, "PageUp"),
+    (KeyCode::PageDown, "PageDown"),
+    (KeyCode::NumPad1, "NumPad1"),
+    (KeyCode::NumPad2, "NumPad2"),
+    (KeyCode::NumPad3, "NumPad3"),
+    (KeyCode::NumPad4, "NumPad4"),
+    (KeyCode::NumPad5, "NumPad5"),
+    (KeyCode::NumPad6, "NumPad6"),
+    (KeyCode::NumPad7, "NumPad7"),
+    (KeyCode::NumPad8, "NumPad8"),
+    (KeyCode::NumPad9, "NumPad9"),
+];
+
+fn key_name(key: Key) -> Option<String> {
+    if key.code == KeyCode::Text {
+        return match key.text() {
+            " " => Some("Space".into()),
+            text => Some(text.into()),
+        };
+    }
+    NAMED_KEYS
+        .iter()
+        .find(|&&(code, _)| code == key.code)
+        .map(|&(_, name)| name.into())
+}
+
+fn is_valid_name(name: &str) -> bool {
+    name == "Space" || name.chars().count() == 1 || NAMED_KEYS.iter().any(|&(_, named)| named == name)
+}
+
+// named keys report shift separately, letters type a capital
+fn shifted(name: &str) -> Option<String> {
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_lowercase() => Some(c.to_uppercase().collect()),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Keymap {
+    bindings: Vec<(String, Action)>,
+}
+
+impl Keymap {
+    pub fn preset(preset: Preset) -> Self {
+        use Action::*;
+        let mut keymap = Keymap { bindings: vec![] };
+        let common = [
+            ("Up", North),
+            ("Down", South),
+            ("Left", West),
+            ("Right", East),
+            ("Home", NorthWest),
+            ("PageUp", NorthEast),
+            ("End", SouthWest),
+            ("PageDown", SouthEast),
+            ("g", PickUp),
+            ("i", Inventory),
+            ("d", Drop),
+            ("t", Throw),
+            ("e", Eat),
+            ("f", Fire),
+            ("z", Cast),
+            ("o", Explore),
+            ("G", TravelToStairs),
+            ("p", Perks),
+            ("<", Descend),
+            ("c", Character),
+            ("x", Look),
+            ("r", Rest),
+            ("M", MessageLog),
+            ("?", Help),
+        ];
+        for &(name, action) in common.iter() {
+            keymap.bind(name, action);
+        }
+        let extra: &[(&str, Action)] = match preset {
+            Preset::Numpad => &[
+                ("NumPad8", North),
+                ("NumPad2", South),
+                ("NumPad4", West),
+                ("NumPad6", East),
+                ("NumPad7", NorthWest),
+                ("NumPad9", NorthEast),
+                ("NumPad1", SouthWest),
+                ("NumPad3", SouthEast),
+                ("NumPad5", Wait),
+            ],
+            Preset::Vi => &[
+                ("k", North),
+                ("j", South),
+                ("h", West),
+                ("l", East),
+                ("y", NorthWest),
+                ("u", NorthEast),
+                ("b", SouthWest),
+                ("n", SouthEast),
+                (".", Wait),
+            ],
+            // the letters taken by movement move their actions elsewhere
+            Preset::Wasd => &[
+                ("w", North),
+                ("s", South),
+                ("a", West),
+                ("d", East),
+                ("q", NorthWest),
+                ("e", NorthEast),
+                ("z", SouthWest),
+                ("c", SouthEast),
+                ("Space", Wait),
+                ("v", Drop),
+                ("n", Eat),
+                ("m", Cast),
+                ("h", Character),
+            ],
+        };
+        for &(name, action) in extra {
+            keymap.bind(name, action);
+        }
+        keymap
+    }
+
+    // returns the action the key did before
+    fn bind(&mut self, name: &str, action: Action) -> Option<Action> {
+        match self.bindings.iter_mut().find(|(bound, _)| bound == name) {
+            Some(binding) => Some(std::mem::replace(&mut binding.1, action)),
+            None => {
+                self.bindings.push((name.into(), action));
+                None
+            }
+        }
+    }
+
+    fn bound(&self, name: &str) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| bound == name)
+            .map(|&(_, action)| action)
+    }
+
+    pub fn action(&self, key: Key) -> Option<(Action, bool)> {
+        let name = key_name(key)?;
+        if let Some(action) = self.bound(&name) {
+            let run = key.code != KeyCode::Text && key.shift && action.direction().is_some();
+            return Some((action, run));
+        }
+        // an unbound capital runs in the direction of its lowercase letter
+        let lower = name.to_lowercase();
+        match self.bound(&lower) {
+            Some(action) if lower != name && action.direction().is_some() => Some((action, true)),
+            _ => None,
+        }
+    }
+
+    // screens waiting for a key press get letters as characters rather than text
+    fn pressed(&self, key: Key) -> Option<Action> {
+        let name = match key.code {
+            KeyCode::Char => key.printable.to_string(),
+            KeyCode::Text => return None,
+            _ => key_name(key)?,
+        };
+        self.bound(&name).or_else(|| self.bound(&name.to_lowercase()))
+    }
+
+    pub fn direction(&self, key: Key) -> Option<(i32, i32)> {
+        self.pressed(key).and_then(|action| action.direction())
+    }
+
+    pub fn is_pressed(&self, key: Key, action: Action) -> bool {
+        self.pressed(key) == Some(action)
+    }
+
+    pub fn keys_for(&self, action: Action) -> Vec<&str> {
+        self.bindings
+            .iter()
+            .filter(|&&(_, bound)| bound == action)
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    fn problems(&self) -> Vec<String> {
+        let mut problems = vec![];
+        for &action in Action::ALL.iter() {
+            if self.keys_for(action).is_empty() {
+                problems.push(format!("Nothing is bound to {} any more.", action.description()));
+            }
+        }
+        for (name, action) in &self.bindings {
+            if action.direction().is_none() {
+                continue;
+            }
+            if let Some(other) = shifted(name).and_then(|capital| self.bound(&capital)) {
+                problems.push(format!(
+                    "Shift+{} is bound to {}, so it can't be used to run.",
+                    name,
+                    other.description()
+                ));
+            }
+        }
+        problems
+    }
+}
+
+pub fn load_keymap() -> (Keymap, Vec<String>) {
+    let json = match fs::read_to_string("keys.json") {
+        Ok(json) => json,
+        Err(_) => return (Keymap::preset(Preset::Numpad), vec![]),
+    };
+    parse_keymap(&json)
+}
+
+fn parse_keymap(json: &str) -> (Keymap, Vec<String>) {
+    let file: KeymapFile = match serde_json::from_str(json) {
+        Ok(file) => file,
+        Err(error) => {
+            let problem = format!("keys.json is invalid ({}), using the default keys.", error);
+            return (Keymap::preset(Preset::Numpad), vec![problem]);
+        }
+    };
+    let mut keymap = Keymap::preset(file.preset);
+    let mut problems = vec![];
+    let mut bindings: Vec<_> = file.bindings.into_iter().collect();
+    bindings.sort_by(|a, b| a.0.cmp(&b.0));
+    for (name, action) in bindings {
+        if !is_valid_name(&name) {
+            problems.push(format!("Unknown key \"{}\" in keys.json.", name));
+            continue;
+        }
+        match keymap.bind(&name, action) {
+            Some(replaced) if replaced != action => problems.push(format!(
+                "\"{}\" was bound to {}, now to {}.",
+                name,
+                replaced.description(),
+                action.description()
+            )),
+            _ => {}
+        }
+    }
+    problems.extend(keymap.problems());
+    (keymap, problems)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the typed text of a key is private, so keys are built up from a blank one
+    fn key(code: KeyCode, shift: bool) -> Key {
+        let mut key = Key::default();
+        key.code = code;
+        key.shift = shift;
+        key
+    }
+
+    fn char_key(printable: char) -> Key {
+        let mut key = key(KeyCode::Char, false);
+        key.printable = printable;
+        key
+    }
+
+    #[test]
+    fn presets_bind_their_movement_keys() {
+        let numpad = Keymap::preset(Preset::Numpad);
+        assert_eq!(numpad.bound("NumPad8"), Some(Action::North));
+        assert_eq!(numpad.bound("k"), None);
+        let vi = Keymap::preset(Preset::Vi);
+        assert_eq!(vi.bound("k"), Some(Action::North));
+        let wasd = Keymap::preset(Preset::Wasd);
+        assert_eq!(wasd.bound("d"), Some(Action::East));
+        assert_eq!(wasd.bound("v"), Some(Action::Drop));
+    }
+
+    #[test]
+    fn presets_leave_no_action_unbound() {
+        for &preset in [Preset::Numpad, Preset::Vi, Preset::Wasd].iter() {
+            assert_eq!(Keymap::preset(preset).problems(), Vec::<String>::new());
+        }
+    }
+
+    #[test]
+    fn file_bindings_go_on_top_of_the_preset() {
+        let (keymap, problems) = parse_keymap(r#"{ "preset": "Vi", "bindings": { "Space": "Wait" } }"#);
+        assert_eq!(keymap.bound("Space"), Some(Action::Wait));
+        assert_eq!(keymap.bound("."), Some(Action::Wait));
+        assert!(problems.is_empty());
+    }
+
+    #[test]
+    fn rebinding_a_key_is_reported() {
+        let (keymap, problems) = parse_keymap(r#"{ "preset": "Numpad", "bindings": { "g": "Rest" } }"#);
+        assert_eq!(keymap.bound("g"), Some(Action::Rest));
+        assert!(problems.iter().any(|problem| problem.starts_with("\"g\" was bound to")));
+        assert!(problems
+            .iter()
+            .any(|problem| problem.starts_with("Nothing is bound to")));
+    }
+
+    #[test]
+    fn unknown_key_names_are_skipped() {
+        let (keymap, problems) = parse_keymap(r#"{ "preset": "Numpad", "bindings": { "Banana": "Wait" } }"#);
+        assert_eq!(keymap.bound("Banana"), None);
+        assert_eq!(problems, vec!["Unknown key \"Banana\" in keys.json.".to_string()]);
+    }
+
+    #[test]
+    fn capitals_that_block_running_are_reported() {
+        let (_, problems) = parse_keymap(r#"{ "preset": "Vi", "bindings": { "K": "Look" } }"#);
+        assert!(problems
+            .iter()
+            .any(|problem| problem.starts_with("Shift+k is bound to")));
+    }
+
+    #[test]
+    fn invalid_files_fall_back_to_the_numpad() {
+        let (keymap, problems) = parse_keymap(r#"{ "preset": "Dvorak" }"#);
+        assert_eq!(keymap.bound("NumPad8"), Some(Action::North));
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("keys.json is invalid"));
+    }
+
+    #[test]
+    fn shift_with_a_named_movement_key_runs() {
+        let keymap = Keymap::preset(Preset::Numpad);
+        assert_eq!(keymap.action(key(KeyCode::Up, false)), Some((Action::North, false)));
+        assert_eq!(keymap.action(key(KeyCode::Up, true)), Some((Action::North, true)));
+        assert_eq!(keymap.action(key(KeyCode::Escape, false)), None);
+    }
+
+    #[test]
+    fn waiting_screens_read_letters_as_characters() {
+        let keymap = Keymap::preset(Preset::Vi);
+        assert_eq!(keymap.direction(char_key('h')), Some((-1, 0)));
+        assert_eq!(keymap.direction(char_key('H')), Some((-1, 0)));
+        assert!(keymap.is_pressed(char_key('x'), Action::Look));
+    }
+}
@@ -1,8 +1,10 @@
 use tcod::console::*;
 use tcod::map::Map as FovMap;
 
+mod activity;
 mod constants;
 mod controls;
+mod keymap;
 mod map_generation;
 mod misc;
 mod objects;
@@ -23,6 +25,8 @@ fn main() {
         .title("rusty game totorial")
         .init();
 
+    let (keymap, problems) = keymap::load_keymap();
+    let (affixes, affix_problems) = map_generation::affixes::load_affixes();
     let mut tcod = Tcod {
         root,
         con: Offscreen::new(MAP_WIDTH, MAP_HEIGHT),
@@ -30,7 +34,23 @@ fn main() {
         fov: FovMap::new(MAP_WIDTH, MAP_HEIGHT),
         key: Default::default(),
         mouse: Default::default(),
+        keymap,
+        affixes,
     };
+    if !problems.is_empty() {
+        msgbox(
+            &format!("Problems with the key bindings:\n\n{}", problems.join("\n")),
+            HELP_WIDTH,
+            &mut tcod.root,
+        );
+    }
+    if !affix_problems.is_empty() {
+        msgbox(
+            &format!("Problems with the item affixes:\n\n{}", affix_problems.join("\n")),
+            HELP_WIDTH,
+            &mut tcod.root,
+        );
+    }
 
     main_menu(&mut tcod);
 }
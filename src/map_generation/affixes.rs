@@ -0,0 +1,182 @@
+use std::fs;
+
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::objects::{
+    damage::{Brand, Resistances},
+    equipment::{Equipment, Slot},
+    object::Object,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub enum Position {
+    Prefix,
+    Suffix,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub enum Target {
+    Weapon,
+    Armour,
+    Any,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Affix {
+    pub name: String,
+    pub position: Position,
+    pub target: Target,
+    pub min_level: u32,
+    #[serde(default)]
+    pub power_bonus: i32,
+    #[serde(default)]
+    pub defense_bonus: i32,
+    #[serde(default)]
+    pub hp_bonus: i32,
+    #[serde(default)]
+    pub resistances: Resistances,
+    #[serde(default)]
+    pub brand: Option<Brand>,
+}
+
+impl Affix {
+    fn fits(&self, equipment: &Equipment) -> bool {
+        match self.target {
+            // a power bonus or brand does nothing for what a launcher shoots
+            Target::Weapon => equipment.slot == Slot::RightHand && equipment.launcher.is_none(),
+            // quivers hold ammo, not armour
+            Target::Armour => !matches!(equipment.slot, Slot::RightHand | Slot::Quiver),
+            Target::Any => true,
+        }
+    }
+
+    fn apply(&self, object: &mut Object) {
+        object.name = match self.position {
+            Position::Prefix => format!("{} {}", self.name, object.name),
+            Position::Suffix => format!("{} {}", object.name, self.name),
+        };
+        let equipment = object.equipment.as_mut().unwrap();
+        equipment.power_bonus += self.power_bonus;
+        equipment.defense_bonus += self.defense_bonus;
+        equipment.hp_bonus += self.hp_bonus;
+        equipment.resistances = equipment.resistances.plus(self.resistances);
+        if self.brand.is_some() {
+            equipment.brand = self.brand;
+        }
+    }
+}
+
+pub fn load_affixes() -> (Vec<Affix>, Vec<String>) {
+    let json = match fs::read_to_string("affixes.json") {
+        Ok(json) => json,
+        Err(error) => {
+            let problem = format!("affixes.json can't be read ({}), gear will have no affixes.", error);
+            return (vec![], vec![problem]);
+        }
+    };
+    match serde_json::from_str(&json) {
+        Ok(affixes) => (affixes, vec![]),
+        Err(error) => {
+            let problem = format!("affixes.json is invalid ({}), gear will have no affixes.", error);
+            (vec![], vec![problem])
+        }
+    }
+}
+
+pub fn roll_affix(object: &mut Object, position: Position, level: u32, affixes: &[Affix]) {
+    let equipment = match object.equipment {
+        Some(equipment) => equipment,
+        None => return,
+    };
+    let candidates: Vec<&Affix> = affixes
+        .iter()
+        .filter(|affix| affix.position == position && affix.min_level <= level && affix.fits(&equipment))
+        .collect();
+    if !candidates.is_empty() {
+        let affix = candidates[rand::thread_rng().gen_range(0, candidates.len())];
+        affix.apply(object);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map_generation::make_map::create_item;
+    use crate::objects::item::Item;
+
+    fn affix(position: Position, target: Target, min_level: u32) -> Affix {
+        Affix {
+            name: "of testing".into(),
+            position,
+            target,
+            min_level,
+            power_bonus: 1,
+            defense_bonus: 0,
+            hp_bonus: 0,
+            resistances: Resistances::default(),
+            brand: None,
+        }
+    }
+
+    fn fits(target: Target, item: Item) -> bool {
+        affix(Position::Suffix, target, 1).fits(&create_item(item, 0, 0).equipment.unwrap())
+    }
+
+    #[test]
+    fn weapon_affixes_fit_melee_weapons_only() {
+        assert!(fits(Target::Weapon, Item::Sword));
+        assert!(fits(Target::Weapon, Item::Greatsword));
+        assert!(!fits(Target::Weapon, Item::Bow));
+        assert!(!fits(Target::Weapon, Item::Sling));
+        assert!(!fits(Target::Weapon, Item::Shield));
+        assert!(!fits(Target::Weapon, Item::Arrows));
+    }
+
+    #[test]
+    fn armour_affixes_skip_the_weapon_hand_and_quiver() {
+        assert!(fits(Target::Armour, Item::Shield));
+        assert!(fits(Target::Armour, Item::Helmet));
+        assert!(fits(Target::Armour, Item::Ring));
+        assert!(!fits(Target::Armour, Item::Sword));
+        assert!(!fits(Target::Armour, Item::Crossbow));
+        assert!(!fits(Target::Armour, Item::Bolts));
+    }
+
+    #[test]
+    fn any_affixes_fit_everything() {
+        assert!(fits(Target::Any, Item::Sword));
+        assert!(fits(Target::Any, Item::Cloak));
+    }
+
+    #[test]
+    fn roll_affix_applies_a_fitting_affix() {
+        let affixes = [affix(Position::Prefix, Target::Weapon, 1)];
+        let mut sword = create_item(Item::Sword, 0, 0);
+        let power = sword.equipment.unwrap().power_bonus;
+        roll_affix(&mut sword, Position::Prefix, 1, &affixes);
+        assert_eq!(sword.name, "of testing sword");
+        assert_eq!(sword.equipment.unwrap().power_bonus, power + 1);
+    }
+
+    #[test]
+    fn roll_affix_skips_affixes_too_deep_or_in_the_wrong_place() {
+        let affixes = [
+            affix(Position::Suffix, Target::Weapon, 5),
+            affix(Position::Prefix, Target::Weapon, 1),
+            affix(Position::Suffix, Target::Armour, 1),
+        ];
+        let mut sword = create_item(Item::Sword, 0, 0);
+        roll_affix(&mut sword, Position::Suffix, 4, &affixes);
+        assert_eq!(sword.name, "sword");
+    }
+
+    #[test]
+    fn roll_affix_leaves_non_gear_alone() {
+        let affixes = [affix(Position::Suffix, Target::Any, 1)];
+        let mut potion = create_item(Item::Heal, 0, 0);
+        roll_affix(&mut potion, Position::Suffix, 1, &affixes);
+        assert!(potion.equipment.is_none());
+        assert_eq!(potion.name, "healing potion");
+    }
+}
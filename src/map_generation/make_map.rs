@@ -1,9 +1,12 @@
 use crate::constants::*;
-use crate::objects::{ai::*, equipment::*, fighter::*, item::Item, object::Object};
+use crate::objects::{
+    ai::*, container::Container, damage::*, equipment::*, fighter::*, hunger::Food, item::Item, object::Object,
+};
 use rand::distributions::{IndependentSample, Weighted, WeightedChoice};
 use rand::Rng;
 use tcod::colors::*;
 
+use super::affixes::*;
 use super::rect::Rect;
 use crate::render::*;
 use std::cmp;
@@ -33,6 +36,30 @@ fn create_v_tunnel(y1: i32, y2: i32, x: i32, map: &mut Map) {
     }
 }
 
+// rolled independently, `chance` percent each
+struct Loot {
+    item: Item,
+    chance: u32,
+}
+
+fn roll_loot(table: &[Loot], level: u32, affixes: &[Affix]) -> Vec<Object> {
+    let mut inventory: Vec<Object> = table
+        .iter()
+        .filter(|loot| rand::thread_rng().gen_range(0, 100) < loot.chance)
+        .map(|loot| create_random_item(loot.item, 0, 0, level, affixes))
+        .collect();
+
+    // monsters wear whatever gear fits
+    for inventory_id in 0..inventory.len() {
+        if let Some(slot) = free_slot_for(inventory_id, &inventory) {
+            let equipment = inventory[inventory_id].equipment.as_mut().unwrap();
+            equipment.slot = slot;
+            equipment.equipped = true;
+        }
+    }
+    inventory
+}
+
 fn from_dungeon_level(table: &[Transition], level: u32) -> u32 {
     table
         .iter()
@@ -41,7 +68,174 @@ fn from_dungeon_level(table: &[Transition], level: u32) -> u32 {
         .map_or(0, |transition| transition.value)
 }
 
-fn place_objects(room: Rect, map: &Map, objects: &mut Vec<Object>, level: u32) {
+fn item_chances(level: u32) -> Vec<Weighted<Item>> {
+    vec![
+        Weighted {
+            weight: 20,
+            item: Item::Gold,
+        },
+        Weighted {
+            weight: from_dungeon_level(
+                &[
+                    Transition { level: 1, value: 10 },
+                    Transition { level: 4, value: 15 },
+                    Transition { level: 7, value: 20 },
+                ],
+                level,
+            ),
+            item: Item::Ration,
+        },
+        Weighted {
+            weight: from_dungeon_level(
+                &[Transition { level: 1, value: 15 }, Transition { level: 4, value: 5 }],
+                level,
+            ),
+            item: Item::Apple,
+        },
+        Weighted {
+            weight: 35,
+            item: Item::Heal,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 2, value: 10 }], level),
+            item: Item::FirePotion,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 4, value: 25 }], level),
+            item: Item::Lightning,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 6, value: 25 }], level),
+            item: Item::Fireball,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 2, value: 10 }], level),
+            item: Item::Confuse,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 2, value: 10 }], level),
+            item: Item::Identify,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 3, value: 5 }], level),
+            item: Item::Recharge,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 2, value: 5 }], level),
+            item: Item::EnchantWeapon,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 2, value: 5 }], level),
+            item: Item::EnchantArmour,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 2, value: 5 }], level),
+            item: Item::RemoveCurse,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 3, value: 4 }], level),
+            item: Item::WandLightning,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 6, value: 3 }], level),
+            item: Item::WandFireball,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 4, value: 3 }], level),
+            item: Item::StaffConfusion,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 2, value: 5 }], level),
+            item: Item::Dagger,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 4, value: 5 }], level),
+            item: Item::Sword,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 1, value: 4 }], level),
+            item: Item::Sling,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 3, value: 4 }], level),
+            item: Item::Bow,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 5, value: 3 }], level),
+            item: Item::Crossbow,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 1, value: 6 }], level),
+            item: Item::Stones,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 3, value: 8 }], level),
+            item: Item::Arrows,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 5, value: 6 }], level),
+            item: Item::Bolts,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 8, value: 15 }], level),
+            item: Item::Shield,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 6, value: 5 }], level),
+            item: Item::Greatsword,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 2, value: 10 }], level),
+            item: Item::Helmet,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 3, value: 10 }], level),
+            item: Item::Armour,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 4, value: 8 }], level),
+            item: Item::Gloves,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 2, value: 8 }], level),
+            item: Item::Boots,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 3, value: 5 }], level),
+            item: Item::Cloak,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 5, value: 4 }], level),
+            item: Item::Ring,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 5, value: 4 }], level),
+            item: Item::Amulet,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 2, value: 3 }], level),
+            item: Item::Bag,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 2, value: 2 }], level),
+            item: Item::BookHeal,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 3, value: 2 }], level),
+            item: Item::BookConfuse,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 4, value: 2 }], level),
+            item: Item::BookLightning,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 6, value: 2 }], level),
+            item: Item::BookFireball,
+        },
+    ]
+}
+
+fn place_objects(room: Rect, map: &Map, objects: &mut Vec<Object>, level: u32, affixes: &[Affix]) {
     let max_monsters = from_dungeon_level(
         &[
             Transition { level: 1, value: 2 },
@@ -78,32 +272,7 @@ fn place_objects(room: Rect, map: &Map, objects: &mut Vec<Object>, level: u32) {
         level,
     );
 
-    let item_chances = &mut [
-        Weighted {
-            weight: 35,
-            item: Item::Heal,
-        },
-        Weighted {
-            weight: from_dungeon_level(&[Transition { level: 4, value: 25 }], level),
-            item: Item::Lightning,
-        },
-        Weighted {
-            weight: from_dungeon_level(&[Transition { level: 6, value: 25 }], level),
-            item: Item::Fireball,
-        },
-        Weighted {
-            weight: from_dungeon_level(&[Transition { level: 2, value: 10 }], level),
-            item: Item::Confuse,
-        },
-        Weighted {
-            weight: from_dungeon_level(&[Transition { level: 4, value: 5 }], level),
-            item: Item::Sword,
-        },
-        Weighted {
-            weight: from_dungeon_level(&[Transition { level: 8, value: 15 }], level),
-            item: Item::Shield,
-        },
-    ];
+    let item_chances = &mut item_chances(level);
 
     let monster_choice = WeightedChoice::new(monster_chances);
     let item_choice = WeightedChoice::new(item_chances);
@@ -122,9 +291,36 @@ fn place_objects(room: Rect, map: &Map, objects: &mut Vec<Object>, level: u32) {
                         base_defense: 0,
                         base_power: 4,
                         xp: 35,
+                        resistances: Resistances::none(),
                         on_death: DeathCallback::Monster,
                     });
                     orc.ai = Some(AI::Basic);
+                    orc.food = Some(Food {
+                        nutrition: 250,
+                        poisonous: true,
+                    });
+                    orc.inventory = roll_loot(
+                        &[
+                            Loot {
+                                item: Item::Sword,
+                                chance: 20,
+                            },
+                            Loot {
+                                item: Item::Helmet,
+                                chance: 10,
+                            },
+                            Loot {
+                                item: Item::Heal,
+                                chance: 15,
+                            },
+                            Loot {
+                                item: Item::Gold,
+                                chance: 40,
+                            },
+                        ],
+                        level,
+                        affixes,
+                    );
                     orc
                 }
                 "troll" => {
@@ -135,14 +331,52 @@ fn place_objects(room: Rect, map: &Map, objects: &mut Vec<Object>, level: u32) {
                         base_defense: 2,
                         base_power: 8,
                         xp: 100,
+                        resistances: Resistances::none().with(DamageType::Fire, -50),
                         on_death: DeathCallback::Monster,
                     });
                     troll.ai = Some(AI::Basic);
+                    troll.food = Some(Food {
+                        nutrition: 500,
+                        poisonous: false,
+                    });
+                    troll.inventory = roll_loot(
+                        &[
+                            Loot {
+                                item: Item::Greatsword,
+                                chance: from_dungeon_level(&[Transition { level: 6, value: 10 }], level),
+                            },
+                            Loot {
+                                item: Item::Armour,
+                                chance: 10,
+                            },
+                            Loot {
+                                item: Item::Heal,
+                                chance: 30,
+                            },
+                            Loot {
+                                item: Item::Gold,
+                                chance: 60,
+                            },
+                        ],
+                        level,
+                        affixes,
+                    );
                     troll
                 }
                 _ => unreachable!(),
             };
             monster.alive = true;
+            // gear with an hp bonus raises the maximum, so start at the new full health
+            let hp_bonus: i32 = monster
+                .inventory
+                .iter()
+                .filter_map(|item| item.equipment)
+                .filter(|equipment| equipment.equipped)
+                .map(|equipment| equipment.hp_bonus)
+                .sum();
+            if let Some(fighter) = monster.fighter.as_mut() {
+                fighter.hp = fighter.base_max_hp + hp_bonus;
+            }
             objects.push(monster);
         };
     }
@@ -154,62 +388,349 @@ fn place_objects(room: Rect, map: &Map, objects: &mut Vec<Object>, level: u32) {
         let y = rand::thread_rng().gen_range(room.y1 + 1, room.y2);
 
         if !is_blocked(x, y, map, objects) {
-            let mut item = match item_choice.ind_sample(&mut rand::thread_rng()) {
-                Item::Heal => {
-                    let mut object = Object::new(x, y, '!', "healing potion", VIOLET, false);
-                    object.item = Some(Item::Heal);
-                    object
-                }
-                Item::Lightning => {
-                    let mut object = Object::new(x, y, '#', "lightning scroll", LIGHT_YELLOW, false);
-                    object.item = Some(Item::Lightning);
-                    object
-                }
-                Item::Fireball => {
-                    // create a fireball scroll (10% chance)
-                    let mut object = Object::new(x, y, '#', "scroll of fireball", LIGHT_RED, false);
-                    object.item = Some(Item::Fireball);
-                    object
-                }
-                Item::Confuse => {
-                    let mut object = Object::new(x, y, '#', "confusion scroll", LIGHT_PURPLE, false);
-                    object.item = Some(Item::Confuse);
-                    object
-                }
-                Item::Sword => {
-                    let mut object = Object::new(x, y, '/', "sword", SKY, false);
-                    object.item = Some(Item::Sword);
-                    object.equipment = Some(Equipment {
-                        equipped: false,
-                        slot: Slot::RightHand,
-                        power_bonus: 3,
-                        defense_bonus: 0,
-                        hp_bonus: 0,
-                    });
-                    object
-                }
-                Item::Shield => {
-                    let mut object = Object::new(x, y, '[', "sword", DARKER_ORANGE, false);
-                    object.item = Some(Item::Shield);
-                    object.equipment = Some(Equipment {
-                        equipped: false,
-                        slot: Slot::RightHand,
-                        power_bonus: 0,
-                        defense_bonus: 1,
-                        hp_bonus: 0,
-                    });
-                    object
-                }
-            };
+            let mut item = create_random_item(item_choice.ind_sample(&mut rand::thread_rng()), x, y, level, affixes);
+            if item.item == Some(Item::Gold) {
+                // deeper levels hold bigger piles
+                item.quantity *= level;
+            }
+            item.always_visible = true;
+            objects.push(item);
+        }
+    }
+
+    if rand::thread_rng().gen_range(0, 100) < CHEST_CHANCE {
+        let x = rand::thread_rng().gen_range(room.x1 + 1, room.x2);
+        let y = rand::thread_rng().gen_range(room.y1 + 1, room.y2);
+
+        if !is_blocked(x, y, map, objects) {
+            let mut chest = Object::new(x, y, '&', "chest", DARK_SEPIA, true);
+            chest.container = Some(Container {
+                capacity: MAX_CHEST_ITEMS as usize,
+                locked: rand::thread_rng().gen_range(0, 100) < CHEST_LOCKED_CHANCE,
+                trapped: rand::thread_rng().gen_range(0, 100) < CHEST_TRAPPED_CHANCE,
+            });
+            let num_chest_items = rand::thread_rng().gen_range(1, MAX_CHEST_ITEMS + 1);
+            for _ in 0..num_chest_items {
+                chest.inventory.push(create_random_item(
+                    item_choice.ind_sample(&mut rand::thread_rng()),
+                    0,
+                    0,
+                    level,
+                    affixes,
+                ));
+            }
+            chest.always_visible = true;
+            objects.push(chest);
+        }
+    }
+}
+
+fn place_shop(room: Rect, map: &Map, objects: &mut Vec<Object>, level: u32, affixes: &[Affix]) {
+    let mut shopkeeper = Object::new(room.x1 + 1, room.y1 + 1, '@', "shopkeeper", YELLOW, true);
+    shopkeeper.fighter = Some(Fighter {
+        hp: 100,
+        base_max_hp: 100,
+        base_defense: 5,
+        base_power: 12,
+        xp: 500,
+        resistances: Resistances::none(),
+        on_death: DeathCallback::Monster,
+    });
+    shopkeeper.ai = Some(AI::Shopkeeper);
+    shopkeeper.alive = true;
+    let mut purse = create_item(Item::Gold, 0, 0);
+    purse.quantity *= 10;
+    shopkeeper.inventory.push(purse);
+    objects.push(shopkeeper);
+
+    let mut stock: Vec<_> = item_chances(level)
+        .into_iter()
+        .filter(|chance| chance.item != Item::Gold)
+        .collect();
+    let stock_choice = WeightedChoice::new(&mut stock);
+    for _ in 0..SHOP_STOCK {
+        let x = rand::thread_rng().gen_range(room.x1 + 1, room.x2);
+        let y = rand::thread_rng().gen_range(room.y1 + 1, room.y2);
+
+        let occupied = objects.iter().any(|object| object.pos() == (x, y));
+        if !occupied && !is_blocked(x, y, map, objects) {
+            let mut item = create_random_item(stock_choice.ind_sample(&mut rand::thread_rng()), x, y, level, affixes);
+            item.price = Some(item.value());
             item.always_visible = true;
             objects.push(item);
         }
     }
 }
 
-pub fn make_map(objects: &mut Vec<Object>, level: u32) -> Map {
+fn equipment(slot: Slot, power_bonus: i32, defense_bonus: i32, hp_bonus: i32) -> Equipment {
+    Equipment {
+        slot,
+        equipped: false,
+        two_handed: false,
+        power_bonus,
+        defense_bonus,
+        hp_bonus,
+        resistances: Resistances::none(),
+        launcher: None,
+        ammo: None,
+        brand: None,
+        rarity: Rarity::Common,
+        enchantment: 0,
+        cursed: false,
+        known: true,
+    }
+}
+
+pub fn create_item(item: Item, x: i32, y: i32) -> Object {
+    let mut object = match item {
+        Item::Heal => Object::new(x, y, '!', "healing potion", VIOLET, false),
+        Item::FirePotion => Object::new(x, y, '!', "potion of fire", FLAME, false),
+        Item::Lightning => Object::new(x, y, '#', "lightning scroll", LIGHT_YELLOW, false),
+        Item::Fireball => Object::new(x, y, '#', "scroll of fireball", LIGHT_RED, false),
+        Item::Confuse => Object::new(x, y, '#', "confusion scroll", LIGHT_PURPLE, false),
+        Item::Identify => Object::new(x, y, '#', "scroll of identify", LIGHT_CYAN, false),
+        Item::Recharge => Object::new(x, y, '#', "scroll of recharging", LIGHT_AZURE, false),
+        Item::EnchantWeapon => Object::new(x, y, '#', "scroll of enchant weapon", LIGHT_BLUE, false),
+        Item::EnchantArmour => Object::new(x, y, '#', "scroll of enchant armour", LIGHTEST_BLUE, false),
+        Item::RemoveCurse => Object::new(x, y, '#', "scroll of remove curse", LIGHT_GREEN, false),
+        Item::Ration => {
+            let mut object = Object::new(x, y, '%', "food ration", LIGHT_SEPIA, false);
+            object.food = Some(Food {
+                nutrition: RATION_NUTRITION,
+                poisonous: false,
+            });
+            object
+        }
+        Item::Apple => {
+            let mut object = Object::new(x, y, '%', "apple", LIGHT_RED, false);
+            object.food = Some(Food {
+                nutrition: APPLE_NUTRITION,
+                poisonous: false,
+            });
+            object
+        }
+        Item::WandLightning => {
+            let mut object = Object::new(x, y, '/', "wand of lightning", LIGHT_BLUE, false);
+            object.charges = Some(5);
+            object
+        }
+        Item::WandFireball => {
+            let mut object = Object::new(x, y, '/', "wand of fireball", FLAME, false);
+            object.charges = Some(3);
+            object
+        }
+        Item::StaffConfusion => {
+            let mut object = Object::new(x, y, '|', "staff of confusion", LIGHT_PURPLE, false);
+            object.charges = Some(4);
+            object
+        }
+        Item::Dagger => {
+            let mut object = Object::new(x, y, '-', "dagger", SKY, false);
+            object.equipment = Some(equipment(Slot::RightHand, 2, 0, 0));
+            object
+        }
+        Item::Sword => {
+            let mut object = Object::new(x, y, '/', "sword", SKY, false);
+            object.equipment = Some(equipment(Slot::RightHand, 3, 0, 0));
+            object
+        }
+        Item::Greatsword => {
+            let mut object = Object::new(x, y, '/', "greatsword", LIGHT_SKY, false);
+            object.equipment = Some(Equipment {
+                two_handed: true,
+                ..equipment(Slot::RightHand, 6, 0, 0)
+            });
+            object
+        }
+        Item::Sling => {
+            let mut object = Object::new(x, y, ')', "sling", SEPIA, false);
+            object.equipment = Some(Equipment {
+                launcher: Some(Launcher {
+                    ammo: Ammo::Stone,
+                    damage: 3,
+                    range: 5,
+                }),
+                ..equipment(Slot::RightHand, 0, 0, 0)
+            });
+            object
+        }
+        Item::Bow => {
+            let mut object = Object::new(x, y, ')', "bow", DARK_AMBER, false);
+            object.equipment = Some(Equipment {
+                two_handed: true,
+                launcher: Some(Launcher {
+                    ammo: Ammo::Arrow,
+                    damage: 5,
+                    range: 8,
+                }),
+                ..equipment(Slot::RightHand, 0, 0, 0)
+            });
+            object
+        }
+        Item::Crossbow => {
+            let mut object = Object::new(x, y, ')', "crossbow", DARKER_AMBER, false);
+            object.equipment = Some(Equipment {
+                two_handed: true,
+                launcher: Some(Launcher {
+                    ammo: Ammo::Bolt,
+                    damage: 8,
+                    range: 10,
+                }),
+                ..equipment(Slot::RightHand, 0, 0, 0)
+            });
+            object
+        }
+        Item::Arrows => {
+            let mut object = Object::new(x, y, '{', "arrow", DARK_AMBER, false);
+            object.equipment = Some(Equipment {
+                ammo: Some(Ammo::Arrow),
+                ..equipment(Slot::Quiver, 0, 0, 0)
+            });
+            object.quantity = rand::thread_rng().gen_range(5, 16);
+            object
+        }
+        Item::Bolts => {
+            let mut object = Object::new(x, y, '{', "bolt", DARKER_AMBER, false);
+            object.equipment = Some(Equipment {
+                ammo: Some(Ammo::Bolt),
+                ..equipment(Slot::Quiver, 0, 0, 0)
+            });
+            object.quantity = rand::thread_rng().gen_range(5, 16);
+            object
+        }
+        Item::Stones => {
+            let mut object = Object::new(x, y, '{', "sling stone", GREY, false);
+            object.equipment = Some(Equipment {
+                ammo: Some(Ammo::Stone),
+                ..equipment(Slot::Quiver, 0, 0, 0)
+            });
+            object.quantity = rand::thread_rng().gen_range(5, 16);
+            object
+        }
+        Item::Shield => {
+            let mut object = Object::new(x, y, '[', "shield", DARKER_ORANGE, false);
+            object.equipment = Some(equipment(Slot::LeftHand, 0, 1, 0));
+            object
+        }
+        Item::Helmet => {
+            let mut object = Object::new(x, y, '[', "helmet", LIGHT_GREY, false);
+            object.equipment = Some(equipment(Slot::Head, 0, 1, 0));
+            object
+        }
+        Item::Armour => {
+            let mut object = Object::new(x, y, '[', "leather armour", DARK_ORANGE, false);
+            object.equipment = Some(equipment(Slot::Body, 0, 2, 0));
+            object
+        }
+        Item::Gloves => {
+            let mut object = Object::new(x, y, '[', "gauntlets", GREY, false);
+            object.equipment = Some(equipment(Slot::Hands, 1, 1, 0));
+            object
+        }
+        Item::Boots => {
+            let mut object = Object::new(x, y, '[', "boots", DARK_SEPIA, false);
+            object.equipment = Some(equipment(Slot::Feet, 0, 1, 0));
+            object
+        }
+        Item::Cloak => {
+            let mut object = Object::new(x, y, '(', "fur cloak", SEPIA, false);
+            object.equipment = Some(Equipment {
+                resistances: Resistances::none().with(DamageType::Cold, 50),
+                ..equipment(Slot::Cloak, 0, 1, 0)
+            });
+            object
+        }
+        Item::Ring => {
+            let mut object = Object::new(x, y, '=', "ring of fire resistance", FLAME, false);
+            object.equipment = Some(Equipment {
+                resistances: Resistances::none().with(DamageType::Fire, 50),
+                ..equipment(Slot::LeftRing, 0, 0, 0)
+            });
+            object
+        }
+        Item::Amulet => {
+            let mut object = Object::new(x, y, '"', "amulet of vitality", GOLD, false);
+            object.equipment = Some(equipment(Slot::Amulet, 0, 0, 20));
+            object
+        }
+        Item::Bag => {
+            let mut object = Object::new(x, y, '(', "bag", DARK_SEPIA, false);
+            object.container = Some(Container {
+                capacity: BAG_CAPACITY,
+                locked: false,
+                trapped: false,
+            });
+            object
+        }
+        Item::BookHeal => Object::new(x, y, '+', "book of healing", LIGHT_VIOLET, false),
+        Item::BookLightning => Object::new(x, y, '+', "book of lightning", LIGHT_YELLOW, false),
+        Item::BookConfuse => Object::new(x, y, '+', "book of confusion", LIGHT_PURPLE, false),
+        Item::BookFireball => Object::new(x, y, '+', "book of fireball", LIGHT_RED, false),
+        Item::Gold => {
+            let mut object = Object::new(x, y, '$', "gold piece", GOLD, false);
+            object.quantity = rand::thread_rng().gen_range(5, 21);
+            object
+        }
+    };
+    object.item = Some(item);
+    object
+}
+
+// found gear may be enchanted or cursed, unknown until worn or identified, and magic more often deeper down
+fn create_random_item(item: Item, x: i32, y: i32, level: u32, affixes: &[Affix]) -> Object {
+    let mut object = create_item(item, x, y);
+    if item.is_enchantable() {
+        let rare_chance = from_dungeon_level(
+            &[
+                Transition { level: 3, value: 3 },
+                Transition { level: 5, value: 8 },
+                Transition { level: 8, value: 15 },
+            ],
+            level,
+        );
+        let magic_chance = from_dungeon_level(
+            &[
+                Transition { level: 1, value: 10 },
+                Transition { level: 3, value: 20 },
+                Transition { level: 6, value: 30 },
+            ],
+            level,
+        );
+        let roll = rand::thread_rng().gen_range(0, 100);
+        let rarity = if roll < rare_chance {
+            roll_affix(&mut object, Position::Prefix, level, affixes);
+            roll_affix(&mut object, Position::Suffix, level, affixes);
+            Rarity::Rare
+        } else if roll < rare_chance + magic_chance {
+            let position = if rand::random() {
+                Position::Prefix
+            } else {
+                Position::Suffix
+            };
+            roll_affix(&mut object, position, level, affixes);
+            Rarity::Magic
+        } else {
+            Rarity::Common
+        };
+        object.equipment.as_mut().unwrap().rarity = rarity;
+
+        let equipment = object.equipment.as_mut().unwrap();
+        let roll = rand::thread_rng().gen_range(0, 100);
+        if roll < CURSED_CHANCE {
+            equipment.enchant(-rand::thread_rng().gen_range(1, MAX_ENCHANTMENT + 1));
+            equipment.cursed = true;
+        } else if roll < CURSED_CHANCE + ENCHANTED_CHANCE {
+            equipment.enchant(rand::thread_rng().gen_range(1, MAX_ENCHANTMENT + 1));
+        }
+        equipment.known = false;
+    }
+    object
+}
+
+pub fn make_map(objects: &mut Vec<Object>, level: u32, affixes: &[Affix]) -> Map {
     let mut map = vec![vec![Tile::wall(); MAP_HEIGHT as usize]; MAP_WIDTH as usize];
     let mut rooms = vec![];
+    let mut shop = None;
 
     assert_eq!(&objects[PLAYER] as *const _, &objects[0] as *const _);
     objects.truncate(1);
@@ -226,7 +747,13 @@ pub fn make_map(objects: &mut Vec<Object>, level: u32) -> Map {
 
         if !failed {
             create_room(new_room, &mut map);
-            place_objects(new_room, &map, objects, level);
+            // the first room is where the player starts, so it is never a shop
+            if !rooms.is_empty() && shop.is_none() && rand::thread_rng().gen_range(0, 100) < SHOP_CHANCE {
+                place_shop(new_room, &map, objects, level, affixes);
+                shop = Some(new_room);
+            } else {
+                place_objects(new_room, &map, objects, level, affixes);
+            }
 
             let (new_x, new_y) = new_room.center();
 
@@ -247,6 +774,15 @@ pub fn make_map(objects: &mut Vec<Object>, level: u32) -> Map {
         }
     }
 
+    // marked once the tunnels are dug, since digging resets the tiles it crosses
+    if let Some(shop) = shop {
+        for x in (shop.x1 + 1)..shop.x2 {
+            for y in (shop.y1 + 1)..shop.y2 {
+                map[x as usize][y as usize].shop = true;
+            }
+        }
+    }
+
     let (last_room_x, last_room_y) = rooms[rooms.len() - 1].center();
     let mut stairs = Object::new(last_room_x, last_room_y, '<', "stairs", WHITE, false);
     stairs.always_visible = true;
@@ -1,2 +1,3 @@
+pub mod affixes;
 pub mod make_map;
 mod rect;
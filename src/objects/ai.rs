@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum AI {
     Basic,
+    Shopkeeper,
     Confused { previous_ai: Box<AI>, num_turns: i32 },
 }
 
@@ -12,6 +13,7 @@ pub fn ai_take_turn(monster_id: usize, tcod: &Tcod, game: &mut Game, objects: &m
     if let Some(ai) = objects[monster_id].ai.take() {
         let new_ai = match ai {
             Basic => ai_basic(monster_id, tcod, game, objects),
+            Shopkeeper => ai_shopkeeper(monster_id, game, objects),
             Confused { previous_ai, num_turns } => ai_confused(monster_id, tcod, game, objects, previous_ai, num_turns),
         };
         objects[monster_id].ai = Some(new_ai);
@@ -32,6 +34,17 @@ fn ai_basic(monster_id: usize, tcod: &Tcod, game: &mut Game, objects: &mut [Obje
     AI::Basic
 }
 
+fn ai_shopkeeper(monster_id: usize, game: &mut Game, objects: &mut [Object]) -> AI {
+    let shopkeeper = &objects[monster_id];
+    if shopkeeper.fighter.map_or(0, |f| f.hp) < shopkeeper.max_hp(game) {
+        game.messages
+            .add(format!("The {} gets angry!", shopkeeper.name), RED, Category::Combat);
+        AI::Basic
+    } else {
+        AI::Shopkeeper
+    }
+}
+
 fn ai_confused(
     monster_id: usize,
     _tcod: &Tcod,
@@ -55,8 +68,11 @@ fn ai_confused(
         }
     } else {
         // Back to normal.
-        game.messages
-            .add(format!("The {} is confused no more!", objects[monster_id].name), RED);
+        game.messages.add(
+            format!("The {} is confused no more!", objects[monster_id].name),
+            RED,
+            Category::Combat,
+        );
         *previous_ai
     }
 }
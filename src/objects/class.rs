@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+
+use crate::objects::{
+    damage::Resistances,
+    fighter::{DeathCallback, Fighter},
+    item::Item,
+    spell::Spell,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Class {
+    Warrior,
+    Rogue,
+    Mage,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Boost {
+    MaxHp(i32),
+    Power,
+    Defense,
+    MaxMana(i32),
+    Spell,
+}
+
+impl Class {
+    pub const ALL: [Class; 3] = [Class::Warrior, Class::Rogue, Class::Mage];
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Class::Warrior => "tough and strong, starts with a sword and shield",
+            Class::Rogue => "quick on the draw, starts with a sling and a bag",
+            Class::Mage => "frail but knows the lightning bolt spell",
+        }
+    }
+
+    pub fn fighter(self) -> Fighter {
+        let (hp, defense, power) = match self {
+            Class::Warrior => (120, 2, 3),
+            Class::Rogue => (90, 1, 2),
+            Class::Mage => (70, 0, 1),
+        };
+        Fighter {
+            hp,
+            base_max_hp: hp,
+            base_defense: defense,
+            base_power: power,
+            xp: 0,
+            resistances: Resistances::none(),
+            on_death: DeathCallback::Player,
+        }
+    }
+
+    pub fn max_mana(self) -> i32 {
+        match self {
+            Class::Warrior => 0,
+            Class::Rogue => 5,
+            Class::Mage => 25,
+        }
+    }
+
+    // gear goes on while its slot is free, so the Rogue's sling comes before the dagger
+    pub fn kit(self) -> Vec<Item> {
+        match self {
+            Class::Warrior => vec![Item::Sword, Item::Shield, Item::Ration],
+            Class::Rogue => vec![Item::Sling, Item::Stones, Item::Dagger, Item::Bag, Item::Ration],
+            Class::Mage => vec![Item::Dagger, Item::Heal, Item::Ration],
+        }
+    }
+
+    pub fn spells(self) -> Vec<Spell> {
+        match self {
+            Class::Mage => vec![Spell::Lightning],
+            _ => vec![],
+        }
+    }
+
+    pub fn boosts(self) -> &'static [Boost] {
+        match self {
+            Class::Warrior => &[Boost::MaxHp(30), Boost::Power, Boost::Defense],
+            Class::Rogue => &[Boost::MaxHp(20), Boost::Power, Boost::Defense, Boost::MaxMana(5)],
+            Class::Mage => &[Boost::MaxHp(10), Boost::Defense, Boost::MaxMana(15), Boost::Spell],
+        }
+    }
+}
+
+impl std::fmt::Display for Class {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            Class::Warrior => write!(f, "Warrior"),
+            Class::Rogue => write!(f, "Rogue"),
+            Class::Mage => write!(f, "Mage"),
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use serde::{Deserialize, Serialize};
+
+use crate::objects::{equipment::is_stuck, item::UseResult, perk::Perk, player::*, shop::is_unpaid, *};
+use crate::render::menus::{inventory_menu, menu};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Container {
+    pub capacity: usize,
+    pub locked: bool,
+    pub trapped: bool,
+}
+
+// bags can't go inside other bags
+pub fn has_room_for(container: &Object, item: &Object) -> bool {
+    match container.container {
+        Some(bag) => {
+            item.container.is_none()
+                && (container.inventory.len() < bag.capacity
+                    || container.inventory.iter().any(|other| other.can_stack_with(item)))
+        }
+        None => false,
+    }
+}
+
+pub fn store(item: Object, container: &mut Object) {
+    match container.inventory.iter_mut().find(|other| other.can_stack_with(&item)) {
+        Some(stack) => stack.quantity += item.quantity,
+        None => container.inventory.push(item),
+    }
+}
+
+// false when the lock held
+pub fn open_chest(chest_id: usize, tcod: &mut Tcod, game: &mut Game, objects: &mut [Object]) -> bool {
+    let chest = objects[chest_id].container.unwrap();
+    if chest.locked {
+        let chance = if game.has_perk(Perk::Lockpicking) {
+            LOCKPICKING_CHANCE
+        } else {
+            FORCE_LOCK_CHANCE
+        };
+        if rand::thread_rng().gen_range(0, 100) >= chance {
+            game.messages.add(
+                format!("The {} is locked. You fail to force it open.", objects[chest_id].name),
+                WHITE,
+                Category::System,
+            );
+            return false;
+        }
+        game.messages.add("You force the lock open.", WHITE, Category::System);
+        objects[chest_id].container.as_mut().unwrap().locked = false;
+    }
+    if chest.trapped {
+        // the trap only goes off once
+        objects[chest_id].container.as_mut().unwrap().trapped = false;
+        game.messages.add(
+            format!(
+                "A poison needle pricks you for {} hit points!{}",
+                objects[PLAYER].resisted_damage(CHEST_TRAP_DAMAGE, DamageType::Poison, game),
+                objects[PLAYER].effectiveness(CHEST_TRAP_DAMAGE, DamageType::Poison, game)
+            ),
+            DARK_GREEN,
+            Category::Combat,
+        );
+        objects[PLAYER].take_damage(CHEST_TRAP_DAMAGE, DamageType::Poison, game);
+        if !objects[PLAYER].alive {
+            return true;
+        }
+    }
+
+    loop {
+        if objects[chest_id].inventory.is_empty() {
+            game.messages.add(
+                format!("The {} is empty.", objects[chest_id].name),
+                WHITE,
+                Category::System,
+            );
+            return true;
+        }
+        let options: Vec<_> = objects[chest_id]
+            .inventory
+            .iter()
+            .map(|item| item.stack_name(game))
+            .collect();
+        let header = format!(
+            "The {} contains: (press a key to take an item, any other to close it)\n",
+            objects[chest_id].name
+        );
+        let choice = match menu(&header, &options, INVENTORY_WIDTH, &mut tcod.root) {
+            Some(choice) => choice,
+            None => return true,
+        };
+        if !can_carry(&objects[chest_id].inventory[choice], game) {
+            game.messages
+                .add("You have no room to carry that.", RED, Category::System);
+            return true;
+        }
+        let item = objects[chest_id].inventory.remove(choice);
+        game.messages.add(
+            format!(
+                "You take {} from the {}.",
+                item.description(game),
+                objects[chest_id].name
+            ),
+            GREEN,
+            Category::Loot,
+        );
+        add_to_inventory(item, game);
+    }
+}
+
+pub fn cast_open_bag(inventory_id: usize, tcod: &mut Tcod, game: &mut Game, _objects: &mut [Object]) -> UseResult {
+    let mut bag_id = inventory_id;
+    let mut used = false;
+    loop {
+        let header = format!("{}\n", game.inventory[bag_id].stack_name(game));
+        let choice = menu(
+            &header,
+            &["Put an item in", "Take an item out"],
+            INVENTORY_WIDTH,
+            &mut tcod.root,
+        );
+        match choice {
+            Some(0) => {
+                let item_id = inventory_menu(
+                    game,
+                    "Press the key next to an item to put it in the bag, or any other to cancel.\n",
+                    &mut tcod.root,
+                );
+                if let Some(item_id) = item_id {
+                    if is_stuck(&game.inventory[item_id], &mut game.messages)
+                        || is_unpaid(&game.inventory[item_id], &mut game.messages)
+                    {
+                        continue;
+                    }
+                    if !has_room_for(&game.inventory[bag_id], &game.inventory[item_id]) {
+                        game.messages
+                            .add("That does not fit in the bag.", RED, Category::System);
+                        continue;
+                    }
+                    let mut item = game.inventory.remove(item_id);
+                    if item_id < bag_id {
+                        bag_id -= 1;
+                    }
+                    if item.equipment.is_some() {
+                        item.dequip(&mut game.messages);
+                    }
+                    game.messages.add(
+                        format!("You put {} into the bag.", item.description(game)),
+                        WHITE,
+                        Category::Loot,
+                    );
+                    store(item, &mut game.inventory[bag_id]);
+                    used = true;
+                }
+            }
+            Some(1) => {
+                let options: Vec<_> = game.inventory[bag_id]
+                    .inventory
+                    .iter()
+                    .map(|item| item.stack_name(game))
+                    .collect();
+                if options.is_empty() {
+                    game.messages.add("The bag is empty.", WHITE, Category::System);
+                    continue;
+                }
+                let choice = menu(
+                    "Press the key next to an item to take it out, or any other to cancel.\n",
+                    &options,
+                    INVENTORY_WIDTH,
+                    &mut tcod.root,
+                );
+                if let Some(choice) = choice {
+                    let item = &game.inventory[bag_id].inventory[choice];
+                    let fits =
+                        game.inventory.len() < 26 || game.inventory.iter().any(|other| other.can_stack_with(item));
+                    if !fits {
+                        game.messages.add("Your inventory is full.", RED, Category::System);
+                        continue;
+                    }
+                    let item = game.inventory[bag_id].inventory.remove(choice);
+                    game.messages.add(
+                        format!("You take {} out of the bag.", item.description(game)),
+                        WHITE,
+                        Category::Loot,
+                    );
+                    add_to_inventory(item, game);
+                    used = true;
+                }
+            }
+            _ => break,
+        }
+    }
+    if used {
+        UseResult::UsedAndKept
+    } else {
+        UseResult::Cancelled
+    }
+}
@@ -0,0 +1,142 @@
+use std::cmp;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum DamageType {
+    Physical,
+    Fire,
+    Cold,
+    Lightning,
+    Poison,
+}
+
+impl DamageType {
+    pub const ALL: [DamageType; 5] = [
+        DamageType::Physical,
+        DamageType::Fire,
+        DamageType::Cold,
+        DamageType::Lightning,
+        DamageType::Poison,
+    ];
+}
+
+impl std::fmt::Display for DamageType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            DamageType::Physical => write!(f, "physical"),
+            DamageType::Fire => write!(f, "fire"),
+            DamageType::Cold => write!(f, "cold"),
+            DamageType::Lightning => write!(f, "lightning"),
+            DamageType::Poison => write!(f, "poison"),
+        }
+    }
+}
+
+// percent of each damage type ignored: 100 is immunity, below 0 a weakness
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Resistances {
+    pub physical: i32,
+    pub fire: i32,
+    pub cold: i32,
+    pub lightning: i32,
+    pub poison: i32,
+}
+
+impl Resistances {
+    pub fn none() -> Self {
+        Default::default()
+    }
+
+    pub fn with(mut self, damage_type: DamageType, value: i32) -> Self {
+        *self.get_mut(damage_type) = value;
+        self
+    }
+
+    pub fn plus(self, other: Resistances) -> Self {
+        Resistances {
+            physical: self.physical + other.physical,
+            fire: self.fire + other.fire,
+            cold: self.cold + other.cold,
+            lightning: self.lightning + other.lightning,
+            poison: self.poison + other.poison,
+        }
+    }
+
+    pub fn get(&self, damage_type: DamageType) -> i32 {
+        match damage_type {
+            DamageType::Physical => self.physical,
+            DamageType::Fire => self.fire,
+            DamageType::Cold => self.cold,
+            DamageType::Lightning => self.lightning,
+            DamageType::Poison => self.poison,
+        }
+    }
+
+    fn get_mut(&mut self, damage_type: DamageType) -> &mut i32 {
+        match damage_type {
+            DamageType::Physical => &mut self.physical,
+            DamageType::Fire => &mut self.fire,
+            DamageType::Cold => &mut self.cold,
+            DamageType::Lightning => &mut self.lightning,
+            DamageType::Poison => &mut self.poison,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Brand {
+    pub kind: DamageType,
+    pub damage: i32,
+}
+
+pub fn apply_resistance(damage: i32, resistance: i32) -> i32 {
+    let resistance = cmp::min(resistance, 100);
+    damage * (100 - resistance) / 100
+}
+
+// appended to the message for a hit, and only when resistance or weakness changed its damage
+pub fn effectiveness_message(name: &str, damage_type: DamageType, damage: i32, dealt: i32) -> String {
+    if damage <= 0 || dealt == damage {
+        String::new()
+    } else if dealt <= 0 {
+        format!(" {} is immune to {} damage!", name, damage_type)
+    } else if dealt < damage {
+        format!(" {} resists {} damage.", name, damage_type)
+    } else {
+        format!(" {} is vulnerable to {} damage!", name, damage_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resistance_scales_damage() {
+        assert_eq!(apply_resistance(10, 0), 10);
+        assert_eq!(apply_resistance(10, 50), 5);
+        assert_eq!(apply_resistance(10, -50), 15);
+        assert_eq!(apply_resistance(10, 150), 0);
+    }
+
+    #[test]
+    fn effectiveness_is_only_told_when_it_changed_the_damage() {
+        assert_eq!(effectiveness_message("orc", DamageType::Fire, 10, 10), "");
+        assert_eq!(effectiveness_message("orc", DamageType::Fire, 0, 0), "");
+        assert_eq!(effectiveness_message("orc", DamageType::Fire, -2, -3), "");
+        assert_eq!(
+            effectiveness_message("orc", DamageType::Fire, 10, 5),
+            format!(" orc resists {} damage.", DamageType::Fire)
+        );
+        assert_eq!(
+            effectiveness_message("troll", DamageType::Fire, 10, 15),
+            format!(" troll is vulnerable to {} damage!", DamageType::Fire)
+        );
+        assert_eq!(
+            effectiveness_message("ghost", DamageType::Fire, 10, 0),
+            format!(" ghost is immune to {} damage!", DamageType::Fire)
+        );
+    }
+}
@@ -1,4 +1,9 @@
-use crate::objects::{item::UseResult, *};
+use crate::objects::{
+    damage::{Brand, Resistances},
+    item::UseResult,
+    *,
+};
+use crate::render::messages::Messages;
 
 use serde::{Deserialize, Serialize};
 
@@ -6,9 +11,117 @@ use serde::{Deserialize, Serialize};
 pub struct Equipment {
     pub slot: Slot,
     pub equipped: bool,
+    #[serde(default)]
+    pub two_handed: bool,
     pub power_bonus: i32,
     pub defense_bonus: i32,
     pub hp_bonus: i32,
+    #[serde(default)]
+    pub resistances: Resistances,
+    #[serde(default)]
+    pub launcher: Option<Launcher>,
+    #[serde(default)]
+    pub ammo: Option<Ammo>,
+    #[serde(default)]
+    pub brand: Option<Brand>,
+    #[serde(default = "common")]
+    pub rarity: Rarity,
+    #[serde(default)]
+    pub enchantment: i32,
+    #[serde(default)]
+    pub cursed: bool,
+    #[serde(default = "known")]
+    pub known: bool,
+}
+
+// gear saved before enchantments had nothing to learn
+fn known() -> bool {
+    true
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Rarity {
+    Common,
+    Magic,
+    Rare,
+}
+
+fn common() -> Rarity {
+    Rarity::Common
+}
+
+impl Rarity {
+    pub fn color(self) -> Color {
+        match self {
+            Rarity::Common => WHITE,
+            Rarity::Magic => LIGHT_BLUE,
+            Rarity::Rare => YELLOW,
+        }
+    }
+
+    pub fn price_factor(self) -> u32 {
+        match self {
+            Rarity::Common => 1,
+            Rarity::Magic => 3,
+            Rarity::Rare => 6,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Ammo {
+    Arrow,
+    Bolt,
+    Stone,
+}
+
+impl std::fmt::Display for Ammo {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            Ammo::Arrow => write!(f, "arrows"),
+            Ammo::Bolt => write!(f, "bolts"),
+            Ammo::Stone => write!(f, "stones"),
+        }
+    }
+}
+
+// shots beyond `range` lose a point of damage per extra tile
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Launcher {
+    pub ammo: Ammo,
+    pub damage: i32,
+    pub range: i32,
+}
+
+impl Equipment {
+    pub fn occupies(&self, slot: Slot) -> bool {
+        self.slot == slot || (self.two_handed && (slot == Slot::LeftHand || slot == Slot::RightHand))
+    }
+
+    pub fn location(&self) -> String {
+        if self.two_handed {
+            "both hands".into()
+        } else {
+            self.slot.to_string()
+        }
+    }
+
+    pub fn enchant(&mut self, amount: i32) {
+        self.enchantment += amount;
+        match self.launcher.as_mut() {
+            Some(launcher) => launcher.damage += amount,
+            None if self.slot == Slot::RightHand => self.power_bonus += amount,
+            None => self.defense_bonus += amount,
+        }
+    }
+
+    fn slots(&self) -> Vec<Slot> {
+        if self.two_handed {
+            vec![Slot::RightHand, Slot::LeftHand]
+        } else {
+            vec![self.slot]
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
@@ -16,6 +129,35 @@ pub enum Slot {
     LeftHand,
     RightHand,
     Head,
+    Body,
+    Hands,
+    Feet,
+    Cloak,
+    LeftRing,
+    RightRing,
+    Amulet,
+    Quiver,
+}
+
+impl Slot {
+    // in paper-doll order
+    pub const ALL: [Slot; 11] = [
+        Slot::Head,
+        Slot::Amulet,
+        Slot::Cloak,
+        Slot::Body,
+        Slot::Hands,
+        Slot::RightHand,
+        Slot::LeftHand,
+        Slot::RightRing,
+        Slot::LeftRing,
+        Slot::Feet,
+        Slot::Quiver,
+    ];
+
+    fn is_ring(self) -> bool {
+        self == Slot::LeftRing || self == Slot::RightRing
+    }
 }
 
 impl std::fmt::Display for Slot {
@@ -24,6 +166,14 @@ impl std::fmt::Display for Slot {
             Slot::LeftHand => write!(f, "left hand"),
             Slot::RightHand => write!(f, "right hand"),
             Slot::Head => write!(f, "head"),
+            Slot::Body => write!(f, "body"),
+            Slot::Hands => write!(f, "hands"),
+            Slot::Feet => write!(f, "feet"),
+            Slot::Cloak => write!(f, "cloak"),
+            Slot::LeftRing => write!(f, "left ring finger"),
+            Slot::RightRing => write!(f, "right ring finger"),
+            Slot::Amulet => write!(f, "neck"),
+            Slot::Quiver => write!(f, "quiver"),
         }
     }
 }
@@ -33,22 +183,89 @@ pub fn toggle_equipment(inventory_id: usize, _tcod: &mut Tcod, game: &mut Game,
         Some(equipment) => equipment,
         None => return UseResult::Cancelled,
     };
-    if let Some(current) = get_equipped_in_slot(equipment.slot, &game.inventory) {
-        game.inventory[current].dequip(&mut game.messages);
-    }
     if equipment.equipped {
         game.inventory[inventory_id].dequip(&mut game.messages);
     } else {
-        game.inventory[inventory_id].equip(&mut game.messages);
+        equip_item(inventory_id, &mut game.inventory, &mut game.messages);
     }
     UseResult::UsedAndKept
 }
 
+pub fn equip_item(inventory_id: usize, inventory: &mut [Object], messages: &mut Messages) {
+    let mut equipment = match inventory[inventory_id].equipment {
+        Some(equipment) => equipment,
+        None => return,
+    };
+    if let Some(slot) = free_slot_for(inventory_id, inventory) {
+        equipment.slot = slot;
+    }
+    let mut worn: Vec<usize> = equipment
+        .slots()
+        .into_iter()
+        .filter_map(|slot| get_equipped_in_slot(slot, inventory))
+        .collect();
+    // a two-handed weapon shows up in both hands
+    worn.dedup();
+    // nothing comes off unless everything in the way can
+    if worn.iter().any(|&current| is_stuck(&inventory[current], messages)) {
+        return;
+    }
+    for current in worn {
+        inventory[current].dequip(messages);
+    }
+    inventory[inventory_id].equipment = Some(equipment);
+    inventory[inventory_id].equip(messages);
+}
+
+// rings go on whichever hand is free
+pub fn free_slot_for(inventory_id: usize, inventory: &[Object]) -> Option<Slot> {
+    let equipment = inventory[inventory_id].equipment?;
+    let candidates = if equipment.slot.is_ring() {
+        vec![Slot::LeftRing, Slot::RightRing]
+    } else {
+        vec![equipment.slot]
+    };
+    candidates.into_iter().find(|&slot| {
+        Equipment { slot, ..equipment }.slots().iter().all(|&needed| {
+            get_equipped_in_slot(needed, inventory)
+                .filter(|&current| current != inventory_id)
+                .is_none()
+        })
+    })
+}
+
 pub fn get_equipped_in_slot(slot: Slot, inventory: &[Object]) -> Option<usize> {
     for (inventory_id, item) in inventory.iter().enumerate() {
-        if item.equipment.as_ref().map_or(false, |e| e.equipped && e.slot == slot) {
+        if item
+            .equipment
+            .as_ref()
+            .map_or(false, |e| e.equipped && e.occupies(slot))
+        {
             return Some(inventory_id);
         }
     }
     None
 }
+
+pub fn is_stuck(item: &Object, messages: &mut Messages) -> bool {
+    let stuck = matches!(item.equipment, Some(equipment) if equipment.equipped && equipment.cursed);
+    if stuck {
+        messages.add(
+            format!("You can't remove the {}, it is cursed!", item.name),
+            RED,
+            Category::System,
+        );
+    }
+    stuck
+}
+
+pub fn paper_doll(inventory: &[Object]) -> String {
+    Slot::ALL
+        .iter()
+        .map(|&slot| {
+            let worn = get_equipped_in_slot(slot, inventory).map_or("-".into(), |id| inventory[id].name.clone());
+            format!("{:>17}: {}", slot.to_string(), worn)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use crate::objects::*;
+use crate::objects::{damage::Resistances, hunger::Hunger, *};
 
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Fighter {
@@ -9,6 +9,8 @@ pub struct Fighter {
     pub base_defense: i32,
     pub base_power: i32,
     pub xp: i32,
+    #[serde(default)]
+    pub resistances: Resistances,
     pub on_death: DeathCallback,
 }
 
@@ -31,7 +33,7 @@ impl DeathCallback {
 
 pub fn player_death(player: &mut Object, game: &mut Game) {
     // the game ended!
-    game.messages.add("You died!", RED);
+    game.messages.add("You died!", RED, Category::Combat);
 
     // for added effect, transform the player into a corpse!
     player.char = '%';
@@ -48,7 +50,24 @@ pub fn monster_death(monster: &mut Object, game: &mut Game) {
             monster.fighter.unwrap().xp
         ),
         ORANGE,
+        Category::Combat,
     );
+    if !monster.inventory.is_empty() {
+        let names: Vec<_> = monster.inventory.iter().map(|item| item.description(game)).collect();
+        game.messages.add(
+            format!("{} drops {}.", monster.name, names.join(", ")),
+            YELLOW,
+            Category::Loot,
+        );
+    }
+    for mut item in monster.inventory.drain(..) {
+        if let Some(equipment) = item.equipment.as_mut() {
+            equipment.equipped = false;
+        }
+        item.set_pos(monster.x, monster.y);
+        item.always_visible = true;
+        game.dropped.push(item);
+    }
     monster.char = '%';
     monster.color = DARK_RED;
     monster.blocks = false;
@@ -56,3 +75,15 @@ pub fn monster_death(monster: &mut Object, game: &mut Game) {
     monster.ai = None;
     monster.name = format!("remains of {}", monster.name);
 }
+
+// a starving body does not heal
+pub fn regenerate_health(game: &Game, objects: &mut [Object]) {
+    if Hunger::from_satiation(game.satiation) >= Hunger::Weak {
+        return;
+    }
+    let player = &mut objects[PLAYER];
+    let chance = player.level + player.max_hp(game) / HP_PER_REGEN_CHANCE;
+    if rand::thread_rng().gen_range(0, HP_REGEN_INTERVAL) < chance {
+        player.heal(1, game);
+    }
+}
@@ -1,4 +1,11 @@
+use super::class::Class;
+use super::identification::Appearance;
+use super::item::Item;
 use super::object::Object;
+use super::perk::Perk;
+use super::spell::Spell;
+use crate::activity::Activity;
+use crate::constants::START_SATIATION;
 use crate::render::{messages::Messages, *};
 use serde::{Deserialize, Serialize};
 
@@ -8,4 +15,58 @@ pub struct Game {
     pub messages: Messages,
     pub inventory: Vec<Object>,
     pub dungeon_level: u32,
+    #[serde(default = "warrior")]
+    pub class: Class,
+    #[serde(default)]
+    pub dropped: Vec<Object>,
+    #[serde(default)]
+    pub appearances: Vec<Appearance>,
+    #[serde(default)]
+    pub identified: Vec<Item>,
+    #[serde(default = "start_satiation")]
+    pub satiation: i32,
+    #[serde(default)]
+    pub gold: u32,
+    #[serde(default)]
+    pub mana: i32,
+    #[serde(default)]
+    pub max_mana: i32,
+    #[serde(default)]
+    pub spells: Vec<Spell>,
+    #[serde(default)]
+    pub perks: Vec<Perk>,
+    #[serde(default)]
+    pub perk_points: u32,
+    #[serde(skip)]
+    pub activity: Option<Activity>,
+}
+
+// saves from before classes were played as warriors
+fn warrior() -> Class {
+    Class::Warrior
+}
+
+// a save from before the hunger clock starts it from the top
+fn start_satiation() -> i32 {
+    START_SATIATION
+}
+
+impl Game {
+    pub fn appearance(&self, item: Item) -> Option<&Appearance> {
+        if self.identified.contains(&item) {
+            None
+        } else {
+            self.appearances.iter().find(|appearance| appearance.item == item)
+        }
+    }
+
+    pub fn has_perk(&self, perk: Perk) -> bool {
+        self.perks.contains(&perk)
+    }
+
+    pub fn identify(&mut self, item: Item) {
+        if !self.identified.contains(&item) {
+            self.identified.push(item);
+        }
+    }
 }
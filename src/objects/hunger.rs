@@ -0,0 +1,173 @@
+use std::cmp;
+
+use serde::{Deserialize, Serialize};
+
+use crate::objects::{item::UseResult, player::use_item, *};
+use crate::render::menus::menu;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Food {
+    pub nutrition: i32,
+    pub poisonous: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub enum Hunger {
+    Satiated,
+    Hungry,
+    Weak,
+    Fainting,
+    Starving,
+}
+
+impl Hunger {
+    pub fn from_satiation(satiation: i32) -> Self {
+        if satiation <= 0 {
+            Hunger::Starving
+        } else if satiation <= FAINTING_SATIATION {
+            Hunger::Fainting
+        } else if satiation <= WEAK_SATIATION {
+            Hunger::Weak
+        } else if satiation <= HUNGRY_SATIATION {
+            Hunger::Hungry
+        } else {
+            Hunger::Satiated
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Hunger::Satiated => WHITE,
+            Hunger::Hungry => YELLOW,
+            Hunger::Weak => ORANGE,
+            Hunger::Fainting | Hunger::Starving => RED,
+        }
+    }
+}
+
+impl std::fmt::Display for Hunger {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            Hunger::Satiated => write!(f, "Not hungry"),
+            Hunger::Hungry => write!(f, "Hungry"),
+            Hunger::Weak => write!(f, "Weak"),
+            Hunger::Fainting => write!(f, "Fainting"),
+            Hunger::Starving => write!(f, "Starving"),
+        }
+    }
+}
+
+// true when the player faints and loses turns
+pub fn tick_hunger(game: &mut Game, objects: &mut [Object]) -> bool {
+    let before = Hunger::from_satiation(game.satiation);
+    game.satiation -= 1;
+    let after = Hunger::from_satiation(game.satiation);
+    if after != before {
+        let message = match after {
+            Hunger::Satiated => None,
+            Hunger::Hungry => Some("You are getting hungry."),
+            Hunger::Weak => Some("You feel weak from hunger."),
+            Hunger::Fainting => Some("You are fainting from lack of food!"),
+            Hunger::Starving => Some("You are starving to death!"),
+        };
+        if let Some(message) = message {
+            game.messages.add(message, after.color(), Category::System);
+        }
+    }
+
+    match after {
+        Hunger::Starving => {
+            objects[PLAYER].take_damage(STARVATION_DAMAGE, DamageType::Physical, game);
+            false
+        }
+        Hunger::Fainting if rand::thread_rng().gen_range(0, 100) < FAINT_CHANCE => {
+            game.messages.add("You faint from hunger!", RED, Category::System);
+            true
+        }
+        _ => false,
+    }
+}
+
+pub fn eat(food: Food, name: &str, game: &mut Game, objects: &mut [Object]) {
+    game.messages
+        .add(format!("You eat the {}.", name), GREEN, Category::System);
+    game.satiation = cmp::min(game.satiation + food.nutrition, MAX_SATIATION);
+    if game.satiation == MAX_SATIATION {
+        game.messages.add("You are completely full.", GREEN, Category::System);
+    }
+    if food.poisonous {
+        game.messages.add(
+            format!(
+                "Ugh, the {} was poisonous! You lose {} hit points.{}",
+                name,
+                objects[PLAYER].resisted_damage(POISONOUS_FOOD_DAMAGE, DamageType::Poison, game),
+                objects[PLAYER].effectiveness(POISONOUS_FOOD_DAMAGE, DamageType::Poison, game)
+            ),
+            DARK_GREEN,
+            Category::Combat,
+        );
+        objects[PLAYER].take_damage(POISONOUS_FOOD_DAMAGE, DamageType::Poison, game);
+    }
+}
+
+pub fn cast_eat(inventory_id: usize, _tcod: &mut Tcod, game: &mut Game, objects: &mut [Object]) -> UseResult {
+    match game.inventory[inventory_id].food {
+        Some(food) => {
+            let name = game.inventory[inventory_id].name.clone();
+            eat(food, &name, game, objects);
+            UseResult::UsedUp
+        }
+        None => UseResult::Cancelled,
+    }
+}
+
+pub fn eat_something(tcod: &mut Tcod, game: &mut Game, objects: &mut Vec<Object>) -> bool {
+    // unpaid shop stock has to be bought first
+    let on_floor = objects.iter().position(|object| {
+        object.pos() == objects[PLAYER].pos()
+            && object.food.is_some()
+            && object.fighter.is_none()
+            && object.price.is_none()
+    });
+    let edible: Vec<usize> = (0..game.inventory.len())
+        .filter(|&id| game.inventory[id].food.is_some())
+        .collect();
+    if on_floor.is_none() && edible.is_empty() {
+        game.messages.add("You have nothing to eat.", RED, Category::System);
+        return false;
+    }
+    // food lying here is offered first, next to what is in the pack
+    let mut options: Vec<_> = on_floor
+        .iter()
+        .map(|&id| format!("{} (on the floor)", objects[id].stack_name(game)))
+        .collect();
+    options.extend(edible.iter().map(|&id| game.inventory[id].stack_name(game)));
+    let choice = menu(
+        "Choose something to eat, or any other key to cancel.\n",
+        &options,
+        INVENTORY_WIDTH,
+        &mut tcod.root,
+    );
+    match (choice, on_floor) {
+        (Some(0), Some(id)) => {
+            let food = objects[id].food.unwrap();
+            let name = objects[id].name.clone();
+            if objects[id].quantity > 1 {
+                objects[id].quantity -= 1;
+            } else {
+                objects.swap_remove(id);
+            }
+            eat(food, &name, game, objects);
+            true
+        }
+        (Some(choice), Some(_)) => {
+            use_item(edible[choice - 1], tcod, game, objects);
+            true
+        }
+        (Some(choice), None) => {
+            use_item(edible[choice], tcod, game, objects);
+            true
+        }
+        (None, _) => false,
+    }
+}
@@ -0,0 +1,35 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tcod::colors::Color;
+
+use crate::constants::*;
+use crate::objects::item::Item;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Appearance {
+    pub item: Item,
+    pub name: String,
+    pub color: Color,
+}
+
+pub fn random_appearances() -> Vec<Appearance> {
+    let mut potions = POTION_APPEARANCES.to_vec();
+    rand::thread_rng().shuffle(&mut potions);
+    let mut labels = SCROLL_LABELS.to_vec();
+    rand::thread_rng().shuffle(&mut labels);
+
+    let potions = Item::POTIONS
+        .iter()
+        .zip(potions)
+        .map(|(&item, (name, color))| Appearance {
+            item,
+            name: format!("{} potion", name),
+            color,
+        });
+    let scrolls = Item::SCROLLS.iter().zip(labels).map(|(&item, label)| Appearance {
+        item,
+        name: format!("scroll labelled {}", label),
+        color: UNIDENTIFIED_SCROLL_COLOR,
+    });
+    potions.chain(scrolls).collect()
+}
@@ -1,15 +1,195 @@
-use crate::objects::*;
+use crate::objects::{equipment::*, perk::Perk, *};
+use crate::render::{look::move_cursor, menus::menu};
 use serde::{Deserialize, Serialize};
 use tcod::input::{self, Event};
+use tcod::line::Line;
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Item {
     Heal,
+    FirePotion,
     Lightning,
     Confuse,
     Fireball,
+    Identify,
+    Recharge,
+    EnchantWeapon,
+    EnchantArmour,
+    RemoveCurse,
+    Ration,
+    Apple,
+    WandLightning,
+    WandFireball,
+    StaffConfusion,
+    Dagger,
     Sword,
+    Greatsword,
+    Sling,
+    Bow,
+    Crossbow,
+    Arrows,
+    Bolts,
+    Stones,
     Shield,
+    Helmet,
+    Armour,
+    Gloves,
+    Boots,
+    Cloak,
+    Ring,
+    Amulet,
+    Bag,
+    BookHeal,
+    BookLightning,
+    BookConfuse,
+    BookFireball,
+    Gold,
+}
+
+impl Item {
+    pub const POTIONS: &'static [Item] = &[Item::Heal, Item::FirePotion];
+    pub const SCROLLS: &'static [Item] = &[
+        Item::Lightning,
+        Item::Confuse,
+        Item::Fireball,
+        Item::Identify,
+        Item::Recharge,
+        Item::EnchantWeapon,
+        Item::EnchantArmour,
+        Item::RemoveCurse,
+    ];
+
+    pub fn is_stackable(self) -> bool {
+        use Item::*;
+        matches!(
+            self,
+            Heal | FirePotion
+                | Lightning
+                | Confuse
+                | Fireball
+                | Identify
+                | Recharge
+                | EnchantWeapon
+                | EnchantArmour
+                | RemoveCurse
+                | Ration
+                | Apple
+                | Arrows
+                | Bolts
+                | Stones
+                | Gold
+        )
+    }
+
+    pub fn category(self) -> ItemCategory {
+        use Item::*;
+        match self {
+            Dagger | Sword | Greatsword | Sling | Bow | Crossbow => ItemCategory::Weapons,
+            Arrows | Bolts | Stones => ItemCategory::Ammunition,
+            Shield | Helmet | Armour | Gloves | Boots | Cloak => ItemCategory::Armour,
+            Ring | Amulet => ItemCategory::Jewellery,
+            Heal | FirePotion => ItemCategory::Potions,
+            Lightning | Confuse | Fireball | Identify | Recharge | EnchantWeapon | EnchantArmour | RemoveCurse => {
+                ItemCategory::Scrolls
+            }
+            WandLightning | WandFireball | StaffConfusion => ItemCategory::Wands,
+            BookHeal | BookLightning | BookConfuse | BookFireball => ItemCategory::Books,
+            Ration | Apple => ItemCategory::Food,
+            Bag | Gold => ItemCategory::Other,
+        }
+    }
+
+    pub fn is_enchantable(self) -> bool {
+        use Item::*;
+        matches!(
+            self,
+            Dagger | Sword | Greatsword | Sling | Bow | Crossbow | Shield | Helmet | Armour | Gloves | Boots | Cloak
+        )
+    }
+
+    // shopkeepers buy back for half
+    pub fn value(self) -> u32 {
+        use Item::*;
+        match self {
+            Heal | FirePotion => 30,
+            Lightning => 60,
+            Confuse => 50,
+            Fireball | Recharge | RemoveCurse => 80,
+            EnchantWeapon | EnchantArmour => 100,
+            Identify => 40,
+            Ration => 15,
+            Apple => 5,
+            WandLightning | WandFireball => 150,
+            StaffConfusion => 120,
+            Dagger | Sling => 20,
+            Sword | Bow | Armour => 60,
+            Greatsword => 120,
+            Crossbow => 100,
+            Arrows | Bolts | Stones | Gold => 1,
+            Shield | Gloves => 40,
+            Helmet | Boots => 30,
+            Cloak => 50,
+            Ring | Amulet => 150,
+            Bag => 100,
+            BookHeal | BookConfuse => 200,
+            BookLightning => 250,
+            BookFireball => 300,
+        }
+    }
+}
+
+// inventory headings, in the order they are listed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ItemCategory {
+    Weapons,
+    Ammunition,
+    Armour,
+    Jewellery,
+    Potions,
+    Scrolls,
+    Wands,
+    Books,
+    Food,
+    Other,
+}
+
+impl std::fmt::Display for ItemCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            ItemCategory::Weapons => write!(f, "Weapons"),
+            ItemCategory::Ammunition => write!(f, "Ammunition"),
+            ItemCategory::Armour => write!(f, "Armour"),
+            ItemCategory::Jewellery => write!(f, "Jewellery"),
+            ItemCategory::Potions => write!(f, "Potions"),
+            ItemCategory::Scrolls => write!(f, "Scrolls"),
+            ItemCategory::Wands => write!(f, "Wands"),
+            ItemCategory::Books => write!(f, "Books"),
+            ItemCategory::Food => write!(f, "Food"),
+            ItemCategory::Other => write!(f, "Other"),
+        }
+    }
+}
+
+pub fn stack_name(name: &str, quantity: u32) -> String {
+    if quantity == 1 {
+        return name.into();
+    }
+    // "scroll of fireball" pluralises the noun before "of", anything else the last word
+    let plural = match name.find(" of ").or_else(|| name.find(" labelled ")) {
+        Some(index) => format!("{}s{}", &name[..index], &name[index..]),
+        None => format!("{}s", name),
+    };
+    format!("{} {}", quantity, plural)
+}
+
+pub fn description(name: &str, quantity: u32) -> String {
+    if quantity > 1 {
+        stack_name(name, quantity)
+    } else if name.starts_with(|c| "aeiou".contains(c)) {
+        format!("an {}", name)
+    } else {
+        format!("a {}", name)
+    }
 }
 
 pub enum UseResult {
@@ -23,7 +203,12 @@ fn closest_monster(tcod: &Tcod, objects: &[Object], max_range: i32) -> Option<us
     let mut closest_dist = (max_range + 1) as f32;
 
     for (id, object) in objects.iter().enumerate() {
-        if (id != PLAYER) && object.fighter.is_some() && object.ai.is_some() && tcod.fov.is_in_fov(object.x, object.y) {
+        if (id != PLAYER)
+            && object.fighter.is_some()
+            && object.ai.is_some()
+            && object.ai != Some(AI::Shopkeeper)
+            && tcod.fov.is_in_fov(object.x, object.y)
+        {
             let dist = objects[PLAYER].distance_to(object);
             if dist < closest_dist {
                 closest_enemy = Some(id);
@@ -34,34 +219,119 @@ fn closest_monster(tcod: &Tcod, objects: &[Object], max_range: i32) -> Option<us
     closest_enemy
 }
 
-fn target_tile(tcod: &mut Tcod, game: &mut Game, objects: &[Object], max_range: Option<f32>) -> Option<(i32, i32)> {
-    use tcod::input::KeyCode::Escape;
+fn targets_in_range(tcod: &Tcod, objects: &[Object], max_range: Option<f32>) -> Vec<(i32, i32)> {
+    let player = &objects[PLAYER];
+    let mut targets: Vec<&Object> = objects
+        .iter()
+        .skip(PLAYER + 1)
+        .filter(|object| {
+            object.fighter.is_some()
+                && object.ai.is_some()
+                && object.ai != Some(AI::Shopkeeper)
+                && tcod.fov.is_in_fov(object.x, object.y)
+                && !matches!(max_range, Some(range) if player.distance_to(object) > range)
+        })
+        .collect();
+    targets.sort_by(|a, b| player.distance_to(a).partial_cmp(&player.distance_to(b)).unwrap());
+    targets.iter().map(|object| object.pos()).collect()
+}
+
+fn render_targeting(
+    tcod: &mut Tcod,
+    objects: &[Object],
+    cursor: (i32, i32),
+    max_range: Option<f32>,
+    radius: Option<i32>,
+) {
+    let player = &objects[PLAYER];
+    for y in 0..MAP_HEIGHT {
+        for x in 0..MAP_WIDTH {
+            if !tcod.fov.is_in_fov(x, y) {
+                continue;
+            }
+            if matches!(max_range, Some(range) if player.distance(x, y) > range) {
+                tcod.root.set_char_background(x, y, LIGHT_RED, BackgroundFlag::Multiply);
+            }
+            let distance = ((x - cursor.0).pow(2) + (y - cursor.1).pow(2)) as f32;
+            if matches!(radius, Some(radius) if distance.sqrt() <= radius as f32) {
+                tcod.root.set_char_background(x, y, DARK_ORANGE, BackgroundFlag::Set);
+            }
+        }
+    }
+    for (x, y) in Line::new(player.pos(), cursor) {
+        if (x, y) == cursor {
+            break;
+        }
+        tcod.root.set_char_background(x, y, DARKER_YELLOW, BackgroundFlag::Set);
+    }
+    tcod.root
+        .set_char_background(cursor.0, cursor.1, LIGHT_YELLOW, BackgroundFlag::Set);
+}
+
+pub fn target_tile(
+    tcod: &mut Tcod,
+    game: &mut Game,
+    objects: &[Object],
+    max_range: Option<f32>,
+    radius: Option<i32>,
+) -> Option<(i32, i32)> {
+    use tcod::input::KeyCode::*;
+    let targets = targets_in_range(tcod, objects, max_range);
+    let mut next_target = 0;
+    let mut cursor = objects[PLAYER].pos();
+    if let Some(&first) = targets.first() {
+        cursor = first;
+        next_target = 1;
+    }
     loop {
+        render_all(tcod, game, objects, false);
+        render_targeting(tcod, objects, cursor, max_range, radius);
         tcod.root.flush();
+
         let event = input::check_for_event(input::KEY_PRESS | input::MOUSE).map(|e| e.1);
+        tcod.key = Default::default();
         match event {
-            Some(Event::Mouse(m)) => tcod.mouse = m,
+            Some(Event::Mouse(m)) => {
+                tcod.mouse = m;
+                let (x, y) = (m.cx as i32, m.cy as i32);
+                if x < MAP_WIDTH && y < MAP_HEIGHT {
+                    cursor = (x, y);
+                }
+            }
             Some(Event::Key(k)) => tcod.key = k,
-            None => tcod.key = Default::default(),
+            None => {}
         }
-        render_all(tcod, game, objects, false);
 
-        let (x, y) = (tcod.mouse.cx as i32, tcod.mouse.cy as i32);
-        let in_fov = (x < MAP_WIDTH) && (y < MAP_HEIGHT) && tcod.fov.is_in_fov(x, y);
+        let (x, y) = cursor;
+        let in_fov = tcod.fov.is_in_fov(x, y);
         let in_range = max_range.map_or(true, |range| objects[PLAYER].distance(x, y) <= range);
-        if tcod.mouse.lbutton_pressed && in_fov && in_range {
-            return Some((x, y));
+        let confirmed = tcod.key.code == Enter || tcod.key.code == NumPadEnter || tcod.mouse.lbutton_pressed;
+        if confirmed && in_fov && in_range {
+            return Some(cursor);
         }
 
         if tcod.mouse.rbutton_pressed || tcod.key.code == Escape {
             return None;
         }
+        match tcod.key.code {
+            Tab if !targets.is_empty() => {
+                cursor = targets[next_target];
+                next_target = (next_target + 1) % targets.len();
+            }
+            _ => {
+                if let Some(direction) = tcod.keymap.direction(tcod.key) {
+                    cursor = move_cursor(cursor, direction);
+                }
+            }
+        }
+        tcod.mouse.lbutton_pressed = false;
+        tcod.mouse.rbutton_pressed = false;
     }
 }
 
-fn target_monster(tcod: &mut Tcod, game: &mut Game, objects: &[Object], max_range: Option<f32>) -> Option<usize> {
+pub fn target_monster(tcod: &mut Tcod, game: &mut Game, objects: &[Object], max_range: Option<f32>) -> Option<usize> {
     loop {
-        match target_tile(tcod, game, objects, max_range) {
+        match target_tile(tcod, game, objects, max_range, None) {
             Some((x, y)) => {
                 // return the first clicked monster, otherwise continue looping
                 for (id, obj) in objects.iter().enumerate() {
@@ -75,46 +345,93 @@ fn target_monster(tcod: &mut Tcod, game: &mut Game, objects: &[Object], max_rang
     }
 }
 
-pub fn cast_heal(_inventory_id: usize, _tcod: &mut Tcod, game: &mut Game, objects: &mut [Object]) -> UseResult {
+// spells and wands pass an id outside the inventory and are always known
+fn is_unknown(inventory_id: usize, game: &Game) -> bool {
+    matches!(game.inventory.get(inventory_id), Some(item) if item.is_unidentified(game))
+}
+
+// An unknown scroll or potion that can't do anything is used up for nothing:
+// refusing or cancelling would give away what it is.
+fn refuse(inventory_id: usize, game: &mut Game, reason: &str) -> UseResult {
+    if is_unknown(inventory_id, game) {
+        return cancel(inventory_id, game);
+    }
+    game.messages.add(reason, RED, Category::System);
+    UseResult::Cancelled
+}
+
+fn cancel(inventory_id: usize, game: &mut Game) -> UseResult {
+    if is_unknown(inventory_id, game) {
+        game.messages
+            .add("Nothing seems to happen.", LIGHT_GREY, Category::System);
+        return UseResult::UsedUp;
+    }
+    UseResult::Cancelled
+}
+
+pub fn cast_heal(inventory_id: usize, _tcod: &mut Tcod, game: &mut Game, objects: &mut [Object]) -> UseResult {
     // heal the player
     let player = &mut objects[PLAYER];
     if let Some(fighter) = player.fighter {
         if fighter.hp == player.max_hp(game) {
-            game.messages.add("You are already at full health.", RED);
-            return UseResult::Cancelled;
+            return refuse(inventory_id, game, "You are already at full health.");
         }
-        game.messages.add("Your wounds start to feel better!", LIGHT_VIOLET);
-        player.heal(HEAL_AMOUNT, game);
+        game.messages
+            .add("Your wounds start to feel better!", LIGHT_VIOLET, Category::System);
+        let amount = if game.has_perk(Perk::PotionMastery) {
+            HEAL_AMOUNT * 3 / 2
+        } else {
+            HEAL_AMOUNT
+        };
+        player.heal(amount, game);
         return UseResult::UsedUp;
     }
     UseResult::Cancelled
 }
 
-pub fn cast_lightning(_inventory_id: usize, _tcod: &mut Tcod, game: &mut Game, objects: &mut [Object]) -> UseResult {
+pub fn cast_drink_fire(_inventory_id: usize, _tcod: &mut Tcod, game: &mut Game, objects: &mut [Object]) -> UseResult {
+    game.messages.add(
+        format!(
+            "The potion burns your throat for {} hit points!{}",
+            objects[PLAYER].resisted_damage(FIRE_POTION_DAMAGE, DamageType::Fire, game),
+            objects[PLAYER].effectiveness(FIRE_POTION_DAMAGE, DamageType::Fire, game)
+        ),
+        ORANGE,
+        Category::Combat,
+    );
+    objects[PLAYER].take_damage(FIRE_POTION_DAMAGE, DamageType::Fire, game);
+    UseResult::UsedUp
+}
+
+pub fn cast_lightning(inventory_id: usize, _tcod: &mut Tcod, game: &mut Game, objects: &mut [Object]) -> UseResult {
     let monster_id = closest_monster(_tcod, objects, LIGHTNING_RANGE);
     if let Some(monster_id) = monster_id {
         game.messages.add(
             format!(
-                "A lightning bolt stricks the {}, the damage is {} hit points.",
-                objects[monster_id].name, LIGHTNING_DAMAGE
+                "A lightning bolt stricks the {}, the damage is {} hit points.{}",
+                objects[monster_id].name,
+                objects[monster_id].resisted_damage(LIGHTNING_DAMAGE, DamageType::Lightning, game),
+                objects[monster_id].effectiveness(LIGHTNING_DAMAGE, DamageType::Lightning, game)
             ),
             LIGHT_BLUE,
+            Category::Combat,
         );
-        if let Some(xp) = objects[monster_id].take_damage(LIGHTNING_DAMAGE, game) {
+        if let Some(xp) = objects[monster_id].take_damage(LIGHTNING_DAMAGE, DamageType::Lightning, game) {
             objects[PLAYER].fighter.as_mut().unwrap().xp += xp;
         }
         UseResult::UsedUp
     } else {
-        game.messages.add("No enemy is close enough to strike.", RED);
-        UseResult::Cancelled
+        refuse(inventory_id, game, "No enemy is close enough to strike.")
     }
 }
 
-pub fn cast_confuse(_inventory_id: usize, _tcod: &mut Tcod, game: &mut Game, objects: &mut [Object]) -> UseResult {
-    game.messages.add(
-        "Left-click an enemy to confuse it, or right-click to cancel.",
-        LIGHT_CYAN,
-    );
+pub fn cast_confuse(inventory_id: usize, _tcod: &mut Tcod, game: &mut Game, objects: &mut [Object]) -> UseResult {
+    let prompt = if is_unknown(inventory_id, game) {
+        UNKNOWN_TARGET_PROMPT
+    } else {
+        "Choose an enemy to confuse with the cursor or mouse, Enter to confirm, Escape to cancel."
+    };
+    game.messages.add(prompt, LIGHT_CYAN, Category::System);
     let monster_id = target_monster(_tcod, game, objects, Some(CONFUSE_RANGE as f32));
     if let Some(monster_id) = monster_id {
         let old_ai = objects[monster_id].ai.take().unwrap_or(AI::Basic);
@@ -128,22 +445,28 @@ pub fn cast_confuse(_inventory_id: usize, _tcod: &mut Tcod, game: &mut Game, obj
                 objects[monster_id].name
             ),
             LIGHT_GREEN,
+            Category::Combat,
         );
         UseResult::UsedUp
     } else {
-        game.messages.add("No enemy in range to strike.", RED);
-        UseResult::Cancelled
+        refuse(inventory_id, game, "No enemy in range to strike.")
     }
 }
 
-pub fn cast_fireball(_inventory_id: usize, tcod: &mut Tcod, game: &mut Game, objects: &mut [Object]) -> UseResult {
-    game.messages.add(
-        "Left-click a target tile for the fireball, or right-click to cancel.",
-        LIGHT_CYAN,
-    );
-    let (x, y) = match target_tile(tcod, game, objects, None) {
+pub fn cast_fireball(inventory_id: usize, tcod: &mut Tcod, game: &mut Game, objects: &mut [Object]) -> UseResult {
+    // an unknown scroll doesn't show the blast either
+    let (prompt, radius) = if is_unknown(inventory_id, game) {
+        (UNKNOWN_TARGET_PROMPT, None)
+    } else {
+        (
+            "Choose a target tile for the fireball with the cursor or mouse, Enter to confirm, Escape to cancel.",
+            Some(FIREBALL_RADIUS),
+        )
+    };
+    game.messages.add(prompt, LIGHT_CYAN, Category::System);
+    let (x, y) = match target_tile(tcod, game, objects, None, radius) {
         Some(tile_pos) => tile_pos,
-        None => return UseResult::Cancelled,
+        None => return cancel(inventory_id, game),
     };
     game.messages.add(
         format!(
@@ -151,16 +474,23 @@ pub fn cast_fireball(_inventory_id: usize, tcod: &mut Tcod, game: &mut Game, obj
             FIREBALL_RADIUS
         ),
         ORANGE,
+        Category::Combat,
     );
 
     let mut total_xp = 0;
     for (id, obj) in objects.iter_mut().enumerate() {
         if obj.distance(x, y) <= FIREBALL_RADIUS as f32 && obj.fighter.is_some() {
             game.messages.add(
-                format!("The {} gets burned for {} hit points.", obj.name, FIREBALL_DAMAGE),
+                format!(
+                    "The {} gets burned for {} hit points.{}",
+                    obj.name,
+                    obj.resisted_damage(FIREBALL_DAMAGE, DamageType::Fire, game),
+                    obj.effectiveness(FIREBALL_DAMAGE, DamageType::Fire, game)
+                ),
                 ORANGE,
+                Category::Combat,
             );
-            if let Some(xp) = obj.take_damage(FIREBALL_DAMAGE, game) {
+            if let Some(xp) = obj.take_damage(FIREBALL_DAMAGE, DamageType::Fire, game) {
                 if id != PLAYER {
                     total_xp += xp;
                 }
@@ -170,3 +500,137 @@ pub fn cast_fireball(_inventory_id: usize, tcod: &mut Tcod, game: &mut Game, obj
     objects[PLAYER].fighter.as_mut().unwrap().xp += total_xp;
     UseResult::UsedUp
 }
+
+pub fn cast_identify(inventory_id: usize, tcod: &mut Tcod, game: &mut Game, _objects: &mut [Object]) -> UseResult {
+    // the scroll being read gets identified anyway
+    let unknown: Vec<usize> = (0..game.inventory.len())
+        .filter(|&id| id != inventory_id && game.inventory[id].is_unidentified(game))
+        .collect();
+    if unknown.is_empty() {
+        return refuse(inventory_id, game, "You have nothing left to identify.");
+    }
+    let options: Vec<_> = unknown.iter().map(|&id| game.inventory[id].stack_name(game)).collect();
+    let header = if is_unknown(inventory_id, game) {
+        UNKNOWN_ITEM_PROMPT
+    } else {
+        "Choose an item to identify, or any other key to cancel.\n"
+    };
+    let choice = menu(header, &options, INVENTORY_WIDTH, &mut tcod.root);
+    match choice {
+        Some(choice) => {
+            let id = unknown[choice];
+            let old_name = game.inventory[id].known_name(game);
+            game.identify(game.inventory[id].item.unwrap());
+            if let Some(equipment) = game.inventory[id].equipment.as_mut() {
+                equipment.known = true;
+            }
+            let new_name = game.inventory[id].known_name(game);
+            game.messages.add(
+                format!("The {} is {}.", old_name, description(&new_name, 1)),
+                LIGHT_CYAN,
+                Category::Loot,
+            );
+            UseResult::UsedUp
+        }
+        None => cancel(inventory_id, game),
+    }
+}
+
+pub fn cast_recharge(inventory_id: usize, tcod: &mut Tcod, game: &mut Game, _objects: &mut [Object]) -> UseResult {
+    let charged: Vec<usize> = (0..game.inventory.len())
+        .filter(|&id| game.inventory[id].charges.is_some())
+        .collect();
+    if charged.is_empty() {
+        return refuse(inventory_id, game, "You have nothing to recharge.");
+    }
+    let options: Vec<_> = charged.iter().map(|&id| game.inventory[id].stack_name(game)).collect();
+    let header = if is_unknown(inventory_id, game) {
+        UNKNOWN_ITEM_PROMPT
+    } else {
+        "Choose an item to recharge, or any other key to cancel.\n"
+    };
+    let choice = menu(header, &options, INVENTORY_WIDTH, &mut tcod.root);
+    match choice {
+        Some(choice) => {
+            let item = &mut game.inventory[charged[choice]];
+            item.charges = item.charges.map(|charges| charges + RECHARGE_AMOUNT);
+            game.messages.add(
+                format!("The {} hums with new power.", item.name),
+                LIGHT_CYAN,
+                Category::Loot,
+            );
+            UseResult::UsedUp
+        }
+        None => cancel(inventory_id, game),
+    }
+}
+
+pub fn cast_enchant_weapon(
+    inventory_id: usize,
+    _tcod: &mut Tcod,
+    game: &mut Game,
+    _objects: &mut [Object],
+) -> UseResult {
+    let weapon = get_equipped_in_slot(Slot::RightHand, &game.inventory)
+        .filter(|&id| matches!(game.inventory[id].item, Some(item) if item.is_enchantable()));
+    match weapon {
+        Some(weapon) => {
+            let item = &mut game.inventory[weapon];
+            let equipment = item.equipment.as_mut().unwrap();
+            equipment.enchant(1);
+            equipment.cursed = false;
+            game.messages.add(
+                format!("Your {} glows blue for a moment.", item.name),
+                LIGHT_BLUE,
+                Category::Loot,
+            );
+            UseResult::UsedUp
+        }
+        None => refuse(inventory_id, game, "You have no weapon to enchant."),
+    }
+}
+
+pub fn cast_enchant_armour(
+    inventory_id: usize,
+    _tcod: &mut Tcod,
+    game: &mut Game,
+    _objects: &mut [Object],
+) -> UseResult {
+    let armour: Vec<usize> = (0..game.inventory.len())
+        .filter(|&id| {
+            let item = &game.inventory[id];
+            matches!(item.item, Some(kind) if kind.is_enchantable())
+                && matches!(item.equipment, Some(equipment) if equipment.equipped && !equipment.occupies(Slot::RightHand))
+        })
+        .collect();
+    if armour.is_empty() {
+        return refuse(inventory_id, game, "You are not wearing any armour.");
+    }
+    // like the real thing, the scroll picks one piece at random
+    let item = &mut game.inventory[armour[rand::thread_rng().gen_range(0, armour.len())]];
+    let equipment = item.equipment.as_mut().unwrap();
+    equipment.enchant(1);
+    equipment.cursed = false;
+    game.messages.add(
+        format!("Your {} glows silver for a moment.", item.name),
+        LIGHT_BLUE,
+        Category::Loot,
+    );
+    UseResult::UsedUp
+}
+
+pub fn cast_remove_curse(
+    _inventory_id: usize,
+    _tcod: &mut Tcod,
+    game: &mut Game,
+    _objects: &mut [Object],
+) -> UseResult {
+    for item in game.inventory.iter_mut() {
+        if let Some(equipment) = item.equipment.as_mut() {
+            equipment.cursed = false;
+        }
+    }
+    game.messages
+        .add("You feel as if someone is helping you.", LIGHT_CYAN, Category::Loot);
+    UseResult::UsedUp
+}
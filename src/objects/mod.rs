@@ -1,10 +1,19 @@
 pub mod ai;
+pub mod class;
+pub mod container;
+pub mod damage;
 pub mod equipment;
 pub mod fighter;
 pub mod game;
+pub mod hunger;
+pub mod identification;
 pub mod item;
 pub mod object;
+pub mod perk;
 pub mod player;
+pub mod projectile;
+pub mod shop;
+pub mod spell;
 
 use rand::Rng;
 use tcod::colors::*;
@@ -13,10 +22,14 @@ use tcod::input::{Key, Mouse};
 use tcod::map::Map as FovMap;
 
 use crate::constants::*;
+use crate::keymap::Keymap;
+use crate::map_generation::affixes::Affix;
 use crate::misc::mut_two;
+use crate::render::messages::Category;
 use crate::render::*;
 
 use ai::AI;
+use damage::DamageType;
 use game::Game;
 use item::Item;
 use object::Object;
@@ -28,6 +41,8 @@ pub struct Tcod {
     pub fov: FovMap,
     pub key: Key,
     pub mouse: Mouse,
+    pub keymap: Keymap,
+    pub affixes: Vec<Affix>,
 }
 
 fn move_by(id: usize, dx: i32, dy: i32, map: &Map, objects: &mut [Object]) {
@@ -1,11 +1,20 @@
 use serde::{Deserialize, Serialize};
 use tcod::colors::*;
 
-use crate::objects::{equipment::Equipment, fighter::Fighter, *};
+use crate::objects::{
+    container::Container,
+    damage::*,
+    equipment::{is_stuck, Equipment, Rarity},
+    fighter::Fighter,
+    hunger::{Food, Hunger},
+    item::{description, stack_name},
+    perk::Perk,
+    *,
+};
 use crate::render::messages::Messages;
 use game::Game;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Object {
     pub x: i32,
     pub y: i32,
@@ -20,6 +29,23 @@ pub struct Object {
     pub ai: Option<AI>,
     pub item: Option<Item>,
     pub equipment: Option<Equipment>,
+    #[serde(default)]
+    pub inventory: Vec<Object>,
+    #[serde(default = "one")]
+    pub quantity: u32,
+    #[serde(default)]
+    pub charges: Option<u32>,
+    #[serde(default)]
+    pub food: Option<Food>,
+    #[serde(default)]
+    pub container: Option<Container>,
+    #[serde(default)]
+    pub price: Option<u32>,
+}
+
+// anything saved before stacking is a single item
+fn one() -> u32 {
+    1
 }
 
 impl Object {
@@ -38,11 +64,85 @@ impl Object {
             ai: None,
             item: None,
             equipment: None,
+            inventory: vec![],
+            quantity: 1,
+            charges: None,
+            food: None,
+            container: None,
+            price: None,
+        }
+    }
+
+    pub fn known_name(&self, game: &Game) -> String {
+        let name = match self.item.and_then(|item| game.appearance(item)) {
+            Some(appearance) => appearance.name.clone(),
+            None => self.name.clone(),
+        };
+        match self.equipment {
+            Some(equipment) if equipment.known && matches!(self.item, Some(item) if item.is_enchantable()) => {
+                let curse = if equipment.cursed { "cursed " } else { "" };
+                format!("{}{:+} {}", curse, equipment.enchantment, name)
+            }
+            _ => name,
         }
     }
 
-    pub fn draw(&self, con: &mut dyn Console) {
-        con.set_default_foreground(self.color);
+    pub fn rarity(&self) -> Rarity {
+        self.equipment.map_or(Rarity::Common, |equipment| equipment.rarity)
+    }
+
+    pub fn value(&self) -> u32 {
+        self.item.map_or(0, |item| item.value()) * self.quantity * self.rarity().price_factor()
+    }
+
+    pub fn is_unidentified(&self, game: &Game) -> bool {
+        self.item.and_then(|item| game.appearance(item)).is_some()
+            || matches!(self.equipment, Some(equipment) if !equipment.known)
+    }
+
+    pub fn known_color(&self, game: &Game) -> Color {
+        self.item
+            .and_then(|item| game.appearance(item))
+            .map_or(self.color, |appearance| appearance.color)
+    }
+
+    pub fn stack_name(&self, game: &Game) -> String {
+        let name = stack_name(&self.known_name(game), self.quantity);
+        match (self.charges, self.container) {
+            (Some(1), _) => format!("{} (1 charge)", name),
+            (Some(charges), _) => format!("{} ({} charges)", name, charges),
+            (None, Some(_)) if self.item.is_some() => format!("{} ({} items)", name, self.inventory.len()),
+            _ => name,
+        }
+    }
+
+    pub fn description(&self, game: &Game) -> String {
+        description(&self.known_name(game), self.quantity)
+    }
+
+    pub fn can_stack_with(&self, other: &Object) -> bool {
+        match (self.item, other.item) {
+            (Some(item), Some(other_item)) => {
+                item == other_item
+                    && item.is_stackable()
+                    && self.name == other.name
+                    // the price is for the whole stack, so goods for sale stay apart
+                    && self.price.is_none()
+                    && other.price.is_none()
+            }
+            _ => false,
+        }
+    }
+
+    pub fn split(&mut self, count: u32) -> Object {
+        let mut part = self.clone();
+        part.quantity = count;
+        self.quantity -= count;
+        part
+    }
+
+    pub fn draw(&self, con: &mut dyn Console, game: &Game) {
+        con.set_default_foreground(self.known_color(game));
         con.put_char(self.x, self.y, self.char, BackgroundFlag::None);
     }
 
@@ -63,7 +163,8 @@ impl Object {
         self.distance(other.x, other.y)
     }
 
-    pub fn take_damage(&mut self, damage: i32, game: &mut Game) -> Option<i32> {
+    pub fn take_damage(&mut self, damage: i32, damage_type: DamageType, game: &mut Game) -> Option<i32> {
+        let damage = self.resisted_damage(damage, damage_type, game);
         if let Some(fighter) = self.fighter.as_mut() {
             if damage > 0 {
                 fighter.hp -= damage;
@@ -79,21 +180,77 @@ impl Object {
         None
     }
 
+    pub fn resisted_damage(&self, damage: i32, damage_type: DamageType, game: &Game) -> i32 {
+        apply_resistance(damage, self.resistance(damage_type, game))
+    }
+
+    pub fn effectiveness(&self, damage: i32, damage_type: DamageType, game: &Game) -> String {
+        effectiveness_message(
+            &self.name,
+            damage_type,
+            damage,
+            self.resisted_damage(damage, damage_type, game),
+        )
+    }
+
     pub fn attack(&mut self, target: &mut Object, game: &mut Game) {
+        if target.name == "player" && game.has_perk(Perk::Dodge) && rand::thread_rng().gen_range(0, 100) < DODGE_CHANCE
+        {
+            game.messages.add(
+                format!("You dodge the {}'s attack.", self.name),
+                LIGHT_GREEN,
+                Category::Combat,
+            );
+            return;
+        }
         // a simple formula for attack damage (attacker power - defender defense)
         let damage = self.power(game) - target.defense(game);
-        if damage > 0 {
+        let dealt = target.resisted_damage(damage, DamageType::Physical, game);
+        if dealt > 0 {
             game.messages.add(
-                format!("{} attacks {} for {} hit points.", self.name, target.name, damage),
+                format!(
+                    "{} attacks {} for {} hit points.{}",
+                    self.name,
+                    target.name,
+                    dealt,
+                    target.effectiveness(damage, DamageType::Physical, game)
+                ),
                 WHITE,
+                Category::Combat,
             );
-            if let Some(xp) = target.take_damage(damage, game) {
+            if let Some(xp) = target.take_damage(damage, DamageType::Physical, game) {
                 self.fighter.as_mut().unwrap().xp += xp;
             }
+            let brands: Vec<Brand> = self.get_all_equipped(game).iter().filter_map(|e| e.brand).collect();
+            for brand in brands {
+                if !target.alive {
+                    break;
+                }
+                game.messages.add(
+                    format!(
+                        "{} takes {} {} damage.{}",
+                        target.name,
+                        target.resisted_damage(brand.damage, brand.kind, game),
+                        brand.kind,
+                        target.effectiveness(brand.damage, brand.kind, game)
+                    ),
+                    ORANGE,
+                    Category::Combat,
+                );
+                if let Some(xp) = target.take_damage(brand.damage, brand.kind, game) {
+                    self.fighter.as_mut().unwrap().xp += xp;
+                }
+            }
         } else {
             game.messages.add(
-                format!("{} attacks {} but it has no effect!", self.name, target.name),
+                format!(
+                    "{} attacks {} but it has no effect!{}",
+                    self.name,
+                    target.name,
+                    target.effectiveness(damage, DamageType::Physical, game)
+                ),
                 WHITE,
+                Category::Combat,
             );
         }
     }
@@ -110,51 +267,87 @@ impl Object {
 
     pub fn equip(&mut self, messages: &mut Messages) {
         if self.item.is_none() {
-            messages.add(format!("Can't equip {:?} because it's not an Item", self), RED);
+            messages.add(
+                format!("Can't equip {:?} because it's not an Item", self),
+                RED,
+                Category::System,
+            );
             return;
         };
         if let Some(ref mut equipment) = self.equipment {
             if !equipment.equipped {
                 equipment.equipped = true;
-                messages.add(format!("Equipped {} on {}.", self.name, equipment.slot), LIGHT_GREEN)
+                messages.add(
+                    format!("Equipped {} on {}.", self.name, equipment.location()),
+                    LIGHT_GREEN,
+                    Category::Loot,
+                );
+                equipment.known = true;
+                if equipment.cursed {
+                    messages.add(
+                        format!("You feel a malignant aura as the {} binds to you!", self.name),
+                        PURPLE,
+                        Category::Loot,
+                    );
+                }
             } else {
-                messages.add(format!("Can't equip {:?} because it's not an Equipment.", self), RED);
+                messages.add(
+                    format!("Can't equip {:?} because it's not an Equipment.", self),
+                    RED,
+                    Category::System,
+                );
             }
         }
     }
 
     pub fn dequip(&mut self, messages: &mut Messages) {
         if self.item.is_none() {
-            messages.add(format!("Can't dequip {:?} because it's not an Item.", self), RED);
+            messages.add(
+                format!("Can't dequip {:?} because it's not an Item.", self),
+                RED,
+                Category::System,
+            );
             return;
         };
+        if is_stuck(self, messages) {
+            return;
+        }
         if let Some(ref mut equipment) = self.equipment {
             if equipment.equipped {
                 equipment.equipped = false;
                 messages.add(
-                    format!("Dequipped {} from {}.", self.name, equipment.slot),
+                    format!("Dequipped {} from {}.", self.name, equipment.location()),
                     LIGHT_YELLOW,
+                    Category::Loot,
                 );
             }
         } else {
-            messages.add(format!("Can't dequip {:?} because it's not an Equipment.", self), RED);
+            messages.add(
+                format!("Can't dequip {:?} because it's not an Equipment.", self),
+                RED,
+                Category::System,
+            );
         }
     }
 
     fn get_all_equipped(&self, game: &Game) -> Vec<Equipment> {
-        if self.name == "player" {
-            game.inventory
-                .iter()
-                .filter(|item| item.equipment.map_or(false, |e| e.equipped))
-                .map(|item| item.equipment.unwrap())
-                .collect()
+        let inventory = if self.name == "player" {
+            &game.inventory
         } else {
-            vec![]
-        }
+            &self.inventory
+        };
+        inventory
+            .iter()
+            .filter(|item| item.equipment.map_or(false, |e| e.equipped))
+            .map(|item| item.equipment.unwrap())
+            .collect()
     }
 
     pub fn power(&self, game: &Game) -> i32 {
-        let base = self.fighter.map_or(0, |f| f.base_power);
+        let mut base = self.fighter.map_or(0, |f| f.base_power);
+        if self.name == "player" && Hunger::from_satiation(game.satiation) >= Hunger::Weak {
+            base -= WEAK_POWER_PENALTY;
+        }
         let bonus: i32 = self.get_all_equipped(game).iter().map(|e| e.power_bonus).sum();
         base + bonus
     }
@@ -170,4 +363,51 @@ impl Object {
         let bonus: i32 = self.get_all_equipped(game).iter().map(|e| e.hp_bonus).sum();
         base + bonus
     }
+
+    pub fn resistance(&self, damage_type: DamageType, game: &Game) -> i32 {
+        let base = self.fighter.map_or(0, |f| f.resistances.get(damage_type));
+        let bonus: i32 = self
+            .get_all_equipped(game)
+            .iter()
+            .map(|e| e.resistances.get(damage_type))
+            .sum();
+        base + bonus
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map_generation::make_map::create_item;
+
+    #[test]
+    fn same_stackable_items_stack() {
+        let potion = create_item(Item::Heal, 0, 0);
+        assert!(potion.can_stack_with(&create_item(Item::Heal, 3, 4)));
+        assert!(!potion.can_stack_with(&create_item(Item::FirePotion, 0, 0)));
+    }
+
+    #[test]
+    fn gear_never_stacks() {
+        let sword = create_item(Item::Sword, 0, 0);
+        assert!(!sword.can_stack_with(&create_item(Item::Sword, 0, 0)));
+    }
+
+    #[test]
+    fn goods_for_sale_stay_apart() {
+        let potion = create_item(Item::Heal, 0, 0);
+        let mut for_sale = create_item(Item::Heal, 0, 0);
+        for_sale.price = Some(20);
+        assert!(!potion.can_stack_with(&for_sale));
+        assert!(!for_sale.can_stack_with(&potion));
+    }
+
+    #[test]
+    fn split_takes_part_of_a_stack() {
+        let mut stack = create_item(Item::Heal, 0, 0);
+        stack.quantity = 5;
+        let part = stack.split(2);
+        assert_eq!((stack.quantity, part.quantity), (3, 2));
+        assert!(part.can_stack_with(&stack));
+    }
 }
@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+
+use crate::objects::*;
+use crate::render::menus::colored_menu;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Perk {
+    Cleave,
+    Dodge,
+    Regeneration,
+    PotionMastery,
+    Lockpicking,
+    Sprint,
+}
+
+impl Perk {
+    pub const ALL: [Perk; 6] = [
+        Perk::Cleave,
+        Perk::Dodge,
+        Perk::Lockpicking,
+        Perk::Sprint,
+        Perk::Regeneration,
+        Perk::PotionMastery,
+    ];
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Perk::Cleave => "melee attacks also hit another adjacent enemy",
+            Perk::Dodge => "sometimes avoid melee attacks entirely",
+            Perk::Regeneration => "slowly recover hit points over time",
+            Perk::PotionMastery => "healing potions restore half as much again",
+            Perk::Lockpicking => "open locked chests far more easily",
+            Perk::Sprint => "sometimes cover two tiles in a single move",
+        }
+    }
+
+    pub fn prerequisite(self) -> Option<Perk> {
+        match self {
+            Perk::Lockpicking | Perk::Sprint => Some(Perk::Dodge),
+            Perk::PotionMastery => Some(Perk::Regeneration),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Perk {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            Perk::Cleave => write!(f, "Cleave"),
+            Perk::Dodge => write!(f, "Dodge"),
+            Perk::Regeneration => write!(f, "Regeneration"),
+            Perk::PotionMastery => write!(f, "Potion Mastery"),
+            Perk::Lockpicking => write!(f, "Lockpicking"),
+            Perk::Sprint => write!(f, "Sprint"),
+        }
+    }
+}
+
+pub fn choose_perk(tcod: &mut Tcod, game: &mut Game) {
+    while game.perk_points > 0 {
+        let options: Vec<_> = Perk::ALL
+            .iter()
+            .map(|&perk| {
+                let missing = perk.prerequisite().filter(|&needed| !game.has_perk(needed));
+                if game.has_perk(perk) {
+                    (format!("{} (learned)", perk), DARK_GREEN)
+                } else if let Some(needed) = missing {
+                    (format!("{} (needs {}): {}", perk, needed, perk.description()), GREY)
+                } else {
+                    (format!("{}: {}", perk, perk.description()), WHITE)
+                }
+            })
+            .collect();
+        let header = format!(
+            "Choose a perk ({} points left), or any other key to decide later:\n",
+            game.perk_points
+        );
+        let perk = match colored_menu(&header, &options, PERK_SCREEN_WIDTH, &mut tcod.root) {
+            Some(choice) => Perk::ALL[choice],
+            None => return,
+        };
+        if game.has_perk(perk) {
+            game.messages
+                .add(format!("You already know {}.", perk), WHITE, Category::System);
+        } else if let Some(needed) = perk.prerequisite().filter(|&needed| !game.has_perk(needed)) {
+            game.messages.add(
+                format!("You must learn {} before {}.", needed, perk),
+                RED,
+                Category::System,
+            );
+        } else {
+            game.perks.push(perk);
+            game.perk_points -= 1;
+            game.messages
+                .add(format!("You learned {}!", perk), LIGHT_GREEN, Category::System);
+        }
+    }
+}
+
+pub fn tick_perks(game: &mut Game, objects: &mut [Object]) {
+    if game.has_perk(Perk::Regeneration) && rand::thread_rng().gen_range(0, REGENERATION_INTERVAL) == 0 {
+        objects[PLAYER].heal(1, game);
+    }
+}
+
+pub fn perk_list(game: &Game) -> String {
+    if game.perks.is_empty() {
+        return "none".into();
+    }
+    game.perks
+        .iter()
+        .map(|perk| perk.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
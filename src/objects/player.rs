@@ -1,87 +1,241 @@
-use crate::objects::{equipment::*, item::*, *};
+use crate::objects::{
+    class::Boost, container::*, equipment::*, hunger::cast_eat, item::*, perk::*, shop::*, spell::*, *,
+};
 use crate::render::menus::menu;
 
-pub fn player_move_or_attack(dx: i32, dy: i32, game: &mut Game, objects: &mut [Object]) {
+pub fn player_move_or_attack(dx: i32, dy: i32, tcod: &mut Tcod, game: &mut Game, objects: &mut Vec<Object>) {
     let x = objects[PLAYER].x + dx;
     let y = objects[PLAYER].y + dy;
 
     let target_id = objects
         .iter()
         .position(|object| object.fighter.is_some() && object.pos() == (x, y));
+    let chest_id = objects
+        .iter()
+        .position(|object| object.container.is_some() && object.item.is_none() && object.pos() == (x, y));
 
     // Attack when target found
+    if let Some(chest_id) = chest_id {
+        open_chest(chest_id, tcod, game, objects);
+        return;
+    }
+
     match target_id {
+        Some(target_id) if objects[target_id].ai == Some(AI::Shopkeeper) => {
+            trade(tcod, game, objects);
+        }
         Some(target_id) => {
             let (player, target) = mut_two(PLAYER, target_id, objects);
             player.attack(target, game);
+            if game.has_perk(Perk::Cleave) {
+                cleave(target_id, game, objects);
+            }
         }
         None => {
+            let from = objects[PLAYER].pos();
             move_by(PLAYER, dx, dy, &game.map, objects);
+            // a sprint carries on in the same direction if the way is clear
+            let moved = objects[PLAYER].pos() != from;
+            if moved && game.has_perk(Perk::Sprint) && rand::thread_rng().gen_range(0, 100) < SPRINT_CHANCE {
+                move_by(PLAYER, dx, dy, &game.map, objects);
+            }
         }
     }
 }
 
-pub fn pick_item_up(object_id: usize, game: &mut Game, objects: &mut Vec<Object>) {
-    if game.inventory.len() >= 26 {
+fn cleave(target_id: usize, game: &mut Game, objects: &mut [Object]) {
+    let (x, y) = objects[PLAYER].pos();
+    let other_id = (0..objects.len()).find(|&id| {
+        let object = &objects[id];
+        id != PLAYER
+            && id != target_id
+            && object.alive
+            && object.fighter.is_some()
+            && object.ai.is_some()
+            && object.ai != Some(AI::Shopkeeper)
+            && (object.x - x).abs() <= 1
+            && (object.y - y).abs() <= 1
+    });
+    if let Some(other_id) = other_id {
+        game.messages.add("You cleave through!", LIGHT_GREY, Category::Combat);
+        let (player, other) = mut_two(PLAYER, other_id, objects);
+        player.attack(other, game);
+    }
+}
+
+// false when there is no room in the inventory
+pub fn pick_item_up(object_id: usize, game: &mut Game, objects: &mut Vec<Object>) -> bool {
+    if !can_carry(&objects[object_id], game) {
         game.messages.add(
-            format!("Your inventory is full, cannot pick up {}.", objects[object_id].name),
+            format!(
+                "Your inventory is full, cannot pick up {}.",
+                objects[object_id].stack_name(game)
+            ),
             RED,
+            Category::System,
         );
-    } else {
-        let item = objects.swap_remove(object_id);
-        game.messages.add(format!("You picked up a {}!", item.name), GREEN);
+        return false;
+    }
+    // unpaid goods keep their price until paid for or carried out of the shop
+    let item = objects.swap_remove(object_id);
+    game.messages.add(
+        format!("You picked up {}!", item.description(game)),
+        GREEN,
+        Category::Loot,
+    );
+    add_to_inventory(item, game);
+    true
+}
+
+pub fn can_carry(item: &Object, game: &Game) -> bool {
+    item.item == Some(Item::Gold)
+        || game.inventory.len() < 26
+        || game
+            .inventory
+            .iter()
+            .any(|other| other.can_stack_with(item) || (item.price.is_none() && has_room_for(other, item)))
+}
+
+// check `can_carry` first
+pub fn add_to_inventory(item: Object, game: &mut Game) {
+    if item.item == Some(Item::Gold) {
+        game.gold += item.quantity;
+        return;
+    }
+    let stack = game.inventory.iter().position(|other| other.can_stack_with(&item));
+    if let Some(stack) = stack {
+        game.inventory[stack].quantity += item.quantity;
+    } else if game.inventory.len() < 26 {
         let index = game.inventory.len();
-        let slot = item.equipment.map(|e| e.slot);
         game.inventory.push(item);
 
-        if let Some(slot) = slot {
-            if get_equipped_in_slot(slot, &game.inventory).is_none() {
-                game.inventory[index].equip(&mut game.messages);
-            }
+        // unknown gear might be cursed, so it is never put on without asking
+        let known = matches!(game.inventory[index].equipment, Some(equipment) if equipment.known);
+        let paid = game.inventory[index].price.is_none();
+        if known && paid && free_slot_for(index, &game.inventory).is_some() {
+            equip_item(index, &mut game.inventory, &mut game.messages);
         }
+    } else {
+        let bag = game
+            .inventory
+            .iter()
+            .position(|other| has_room_for(other, &item))
+            .unwrap();
+        game.messages.add(
+            format!("It goes into your {}.", game.inventory[bag].name),
+            WHITE,
+            Category::Loot,
+        );
+        store(item, &mut game.inventory[bag]);
     }
 }
 
-pub fn drop_item(inventory_id: usize, game: &mut Game, objects: &mut Vec<Object>) {
-    let mut item = game.inventory.remove(inventory_id);
+pub fn drop_item(inventory_id: usize, count: u32, game: &mut Game, objects: &mut Vec<Object>) {
+    if is_stuck(&game.inventory[inventory_id], &mut game.messages) {
+        return;
+    }
+    let mut item = if count < game.inventory[inventory_id].quantity {
+        game.inventory[inventory_id].split(count)
+    } else {
+        game.inventory.remove(inventory_id)
+    };
     if item.equipment.is_some() {
         item.dequip(&mut game.messages);
     }
     item.set_pos(objects[PLAYER].x, objects[PLAYER].y);
-    game.messages.add(format!("You dropped a {}.", item.name), YELLOW);
-    objects.push(item);
+    game.messages.add(
+        format!("You dropped {}.", item.description(game)),
+        YELLOW,
+        Category::Loot,
+    );
+    place_on_floor(item, objects);
+}
+
+pub fn place_on_floor(item: Object, objects: &mut Vec<Object>) {
+    let stack = objects
+        .iter()
+        .position(|object| object.pos() == item.pos() && object.can_stack_with(&item));
+    match stack {
+        Some(stack) => objects[stack].quantity += item.quantity,
+        None => objects.push(item),
+    }
 }
 
 pub fn use_item(inventory_id: usize, tcod: &mut Tcod, game: &mut Game, objects: &mut [Object]) {
     use Item::*;
+    if is_unpaid(&game.inventory[inventory_id], &mut game.messages) {
+        return;
+    }
+    if game.inventory[inventory_id].charges == Some(0) {
+        game.messages.add(
+            format!("The {} has no charges left.", game.inventory[inventory_id].name),
+            WHITE,
+            Category::System,
+        );
+        return;
+    }
     // just call the "use_function" if it is defined
     if let Some(item) = game.inventory[inventory_id].item {
         let on_use = match item {
             Heal => cast_heal,
+            FirePotion => cast_drink_fire,
             Lightning => cast_lightning,
             Confuse => cast_confuse,
             Fireball => cast_fireball,
-            Sword => toggle_equipment,
-            Shield => toggle_equipment,
+            Identify => cast_identify,
+            Recharge => cast_recharge,
+            EnchantWeapon => cast_enchant_weapon,
+            EnchantArmour => cast_enchant_armour,
+            RemoveCurse => cast_remove_curse,
+            Ration | Apple => cast_eat,
+            WandLightning => cast_lightning,
+            WandFireball => cast_fireball,
+            StaffConfusion => cast_confuse,
+            Bag => cast_open_bag,
+            BookHeal | BookLightning | BookConfuse | BookFireball => cast_learn,
+            Gold => cast_spend_gold,
+            Dagger | Sword | Greatsword | Shield => toggle_equipment,
+            Sling | Bow | Crossbow | Arrows | Bolts | Stones => toggle_equipment,
+            Helmet | Armour | Gloves | Boots | Cloak | Ring | Amulet => toggle_equipment,
         };
-        match on_use(inventory_id, tcod, game, objects) {
+        let name = game.inventory[inventory_id].name.clone();
+        let result = on_use(inventory_id, tcod, game, objects);
+        if !matches!(result, UseResult::Cancelled) && game.appearance(item).is_some() {
+            game.identify(item);
+            game.messages
+                .add(format!("It was {}.", description(&name, 1)), LIGHT_CYAN, Category::Loot);
+        }
+        match result {
             UseResult::UsedUp => {
                 // destroy after use, unless it was cancelled for some reason
-                game.inventory.remove(inventory_id);
+                if let Some(charges) = game.inventory[inventory_id].charges.as_mut() {
+                    *charges -= 1;
+                } else if game.inventory[inventory_id].quantity > 1 {
+                    game.inventory[inventory_id].quantity -= 1;
+                } else {
+                    game.inventory.remove(inventory_id);
+                }
             }
             UseResult::UsedAndKept => {}
             UseResult::Cancelled => {
-                game.messages.add("Cancelled", WHITE);
+                game.messages.add("Cancelled", WHITE, Category::System);
             }
         }
     } else {
         game.messages.add(
             format!("The {} cannot be used.", game.inventory[inventory_id].name),
             WHITE,
+            Category::System,
         );
     }
 }
 
+fn cast_spend_gold(_inventory_id: usize, _tcod: &mut Tcod, game: &mut Game, _objects: &mut [Object]) -> UseResult {
+    game.messages
+        .add("Gold is for spending in shops.", WHITE, Category::System);
+    UseResult::Cancelled
+}
+
 pub fn level_up(tcod: &mut Tcod, game: &mut Game, objects: &mut [Object]) {
     let player = &mut objects[PLAYER];
     let level_up_xp = LEVEL_UP_BASE + player.level * LEVEL_UP_FACTOR;
@@ -90,34 +244,66 @@ pub fn level_up(tcod: &mut Tcod, game: &mut Game, objects: &mut [Object]) {
         game.messages.add(
             format!("Your battle skills grow stronger! You reached level {}!", player.level,),
             YELLOW,
+            Category::System,
         );
         let fighter = player.fighter.as_mut().unwrap();
+        let unknown_spells: Vec<Spell> = Spell::ALL
+            .iter()
+            .cloned()
+            .filter(|spell| !game.spells.contains(spell))
+            .collect();
+        let boosts: Vec<Boost> = game
+            .class
+            .boosts()
+            .iter()
+            .cloned()
+            .filter(|&boost| boost != Boost::Spell || !unknown_spells.is_empty())
+            .collect();
+        let options: Vec<_> = boosts
+            .iter()
+            .map(|boost| match *boost {
+                Boost::MaxHp(amount) => format!("+{}HP (Current: {})", amount, fighter.base_max_hp),
+                Boost::Power => format!("+1ATK (Current: {})", fighter.base_power),
+                Boost::Defense => format!("+1DEF (Current: {})", fighter.base_defense),
+                Boost::MaxMana(amount) => format!("+{}MP (Current: {})", amount, game.max_mana),
+                Boost::Spell => "Learn a new spell".into(),
+            })
+            .collect();
         let mut choice = None;
         while choice.is_none() {
             choice = menu(
                 "Level up! Choose stat to raise:\n",
-                &[
-                    format!("+20HP (Current: {})", fighter.base_max_hp),
-                    format!("+1ATK (Current: {})", fighter.base_power),
-                    format!("+1DEF (Current: {})", fighter.base_defense),
-                ],
+                &options,
                 LEVEL_SCREEN_WIDTH,
                 &mut tcod.root,
             );
         }
         fighter.xp -= level_up_xp;
-        match choice.unwrap() {
-            0 => {
-                fighter.base_max_hp += 20;
+        match boosts[choice.unwrap()] {
+            Boost::MaxHp(amount) => {
+                fighter.base_max_hp += amount;
                 fighter.hp = fighter.base_max_hp;
             }
-            1 => {
+            Boost::Power => {
                 fighter.base_power += 1;
             }
-            2 => {
+            Boost::Defense => {
                 fighter.base_defense += 1;
             }
-            _ => unreachable!(),
+            Boost::MaxMana(amount) => {
+                game.max_mana += amount;
+                game.mana = game.max_mana;
+            }
+            Boost::Spell => {
+                let names: Vec<_> = unknown_spells.iter().map(|spell| spell.to_string()).collect();
+                let mut spell = None;
+                while spell.is_none() {
+                    spell = menu("Choose a spell to learn:\n", &names, LEVEL_SCREEN_WIDTH, &mut tcod.root);
+                }
+                learn_spell(unknown_spells[spell.unwrap()], game);
+            }
         }
+        game.perk_points += 1;
+        choose_perk(tcod, game);
     }
 }
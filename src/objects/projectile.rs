@@ -0,0 +1,274 @@
+use std::cmp;
+
+use tcod::line::Line;
+
+use crate::objects::{equipment::*, item::*, player::place_on_floor, shop::is_unpaid, *};
+
+// stops before walls and at anything solid; returns where it lands and the creature it hit
+pub fn trace_projectile(
+    from: (i32, i32),
+    to: (i32, i32),
+    map: &Map,
+    objects: &[Object],
+) -> ((i32, i32), Option<usize>) {
+    let mut landing = from;
+    for (x, y) in Line::new(from, to) {
+        if map[x as usize][y as usize].blocked {
+            break;
+        }
+        // anything solid stops it, but only creatures get hit; it drops in front of the rest
+        let blocker = objects
+            .iter()
+            .position(|object| object.pos() == (x, y) && object.blocks);
+        match blocker {
+            Some(id) if objects[id].fighter.is_some() => return ((x, y), Some(id)),
+            Some(_) => break,
+            None => landing = (x, y),
+        }
+    }
+    (landing, None)
+}
+
+pub fn throw_item(inventory_id: usize, tcod: &mut Tcod, game: &mut Game, objects: &mut Vec<Object>) -> bool {
+    if is_stuck(&game.inventory[inventory_id], &mut game.messages)
+        || is_unpaid(&game.inventory[inventory_id], &mut game.messages)
+    {
+        return false;
+    }
+    game.messages.add(
+        "Choose a tile to throw at with the cursor or mouse, Enter to confirm, Escape to cancel.",
+        LIGHT_CYAN,
+        Category::System,
+    );
+    let target = match target_tile(tcod, game, objects, Some(THROW_RANGE as f32), None) {
+        Some(tile_pos) => tile_pos,
+        None => return false,
+    };
+
+    let mut item = if game.inventory[inventory_id].quantity > 1 {
+        game.inventory[inventory_id].split(1)
+    } else {
+        game.inventory.remove(inventory_id)
+    };
+    if item.equipment.is_some() {
+        item.dequip(&mut game.messages);
+    }
+    game.messages.add(
+        format!("You throw {}.", item.description(game)),
+        WHITE,
+        Category::Combat,
+    );
+
+    let (landing, hit) = trace_projectile(objects[PLAYER].pos(), target, &game.map, objects);
+
+    if item.item.filter(|kind| Item::POTIONS.contains(kind)).is_some() {
+        shatter(&item, landing, game, objects);
+        return true;
+    }
+
+    if let Some(target_id) = hit {
+        let power = item.equipment.map_or(0, |e| e.power_bonus);
+        let damage = if item.item == Some(Item::Dagger) {
+            power + THROWN_DAGGER_BONUS
+        } else {
+            power
+        } - objects[target_id].defense(game);
+        let dealt = objects[target_id].resisted_damage(damage, DamageType::Physical, game);
+        if dealt > 0 {
+            game.messages.add(
+                format!(
+                    "The {} hits {} for {} hit points.{}",
+                    item.name,
+                    objects[target_id].name,
+                    dealt,
+                    objects[target_id].effectiveness(damage, DamageType::Physical, game)
+                ),
+                WHITE,
+                Category::Combat,
+            );
+            if let Some(xp) = objects[target_id].take_damage(damage, DamageType::Physical, game) {
+                objects[PLAYER].fighter.as_mut().unwrap().xp += xp;
+            }
+        } else {
+            game.messages.add(
+                format!(
+                    "The {} bounces off {}.{}",
+                    item.name,
+                    objects[target_id].name,
+                    objects[target_id].effectiveness(damage, DamageType::Physical, game)
+                ),
+                WHITE,
+                Category::Combat,
+            );
+        }
+    }
+
+    item.set_pos(landing.0, landing.1);
+    item.always_visible = true;
+    place_on_floor(item, objects);
+    true
+}
+
+fn shatter(potion: &Object, (x, y): (i32, i32), game: &mut Game, objects: &mut [Object]) {
+    let kind = potion.item.unwrap();
+    let name = objects
+        .iter()
+        .find(|object| object.pos() == (x, y) && object.fighter.is_some())
+        .map_or("the floor".into(), |object| object.name.clone());
+    game.messages.add(
+        format!("The potion shatters on {}!", name),
+        LIGHT_BLUE,
+        Category::Combat,
+    );
+
+    let mut total_xp = 0;
+    for (id, obj) in objects.iter_mut().enumerate() {
+        if obj.distance(x, y) > SHATTER_RADIUS as f32 || obj.fighter.is_none() {
+            continue;
+        }
+        match kind {
+            Item::Heal => {
+                game.messages
+                    .add(format!("{} looks healthier.", obj.name), LIGHT_VIOLET, Category::Combat);
+                obj.heal(HEAL_AMOUNT / 2, game);
+            }
+            Item::FirePotion => {
+                game.messages.add(
+                    format!(
+                        "{} is splashed with flames for {} hit points.{}",
+                        obj.name,
+                        obj.resisted_damage(FIRE_POTION_DAMAGE, DamageType::Fire, game),
+                        obj.effectiveness(FIRE_POTION_DAMAGE, DamageType::Fire, game)
+                    ),
+                    ORANGE,
+                    Category::Combat,
+                );
+                if let Some(xp) = obj.take_damage(FIRE_POTION_DAMAGE, DamageType::Fire, game) {
+                    if id != PLAYER {
+                        total_xp += xp;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    objects[PLAYER].fighter.as_mut().unwrap().xp += total_xp;
+
+    if game.appearance(kind).is_some() {
+        game.identify(kind);
+        game.messages.add(
+            format!("It was {}.", description(&potion.name, 1)),
+            LIGHT_CYAN,
+            Category::Loot,
+        );
+    }
+}
+
+pub fn fire_weapon(tcod: &mut Tcod, game: &mut Game, objects: &mut Vec<Object>) -> bool {
+    let launcher = game
+        .inventory
+        .iter()
+        .filter_map(|item| item.equipment)
+        .find(|equipment| equipment.equipped && equipment.launcher.is_some())
+        .and_then(|equipment| equipment.launcher);
+    let launcher = match launcher {
+        Some(launcher) => launcher,
+        None => {
+            game.messages
+                .add("You have no ranged weapon ready.", RED, Category::System);
+            return false;
+        }
+    };
+    let quiver = get_equipped_in_slot(Slot::Quiver, &game.inventory)
+        .filter(|&id| game.inventory[id].equipment.and_then(|e| e.ammo) == Some(launcher.ammo));
+    let quiver = match quiver {
+        Some(quiver) => quiver,
+        None => {
+            game.messages.add(
+                format!("You have no {} in your quiver.", launcher.ammo),
+                RED,
+                Category::System,
+            );
+            return false;
+        }
+    };
+
+    game.messages.add(
+        "Choose an enemy to shoot at with the cursor or mouse, Enter to confirm, Escape to cancel.",
+        LIGHT_CYAN,
+        Category::System,
+    );
+    let max_range = launcher.range * LONG_RANGE_FACTOR;
+    let target_id = match target_monster(tcod, game, objects, Some(max_range as f32)) {
+        Some(target_id) => target_id,
+        None => return false,
+    };
+
+    let mut shot = if game.inventory[quiver].quantity > 1 {
+        game.inventory[quiver].split(1)
+    } else {
+        game.inventory.remove(quiver)
+    };
+    if let Some(equipment) = shot.equipment.as_mut() {
+        equipment.equipped = false;
+    }
+
+    let (landing, hit) = trace_projectile(objects[PLAYER].pos(), objects[target_id].pos(), &game.map, objects);
+    match hit {
+        Some(hit_id) => {
+            if hit_id != target_id {
+                game.messages.add(
+                    format!("{} is in the line of fire!", objects[hit_id].name),
+                    WHITE,
+                    Category::Combat,
+                );
+            }
+            // shots past the launcher's range lose strength with every extra tile
+            let distance = objects[PLAYER].distance_to(&objects[hit_id]) as i32;
+            let penalty = cmp::max(0, distance - launcher.range);
+            let damage = launcher.damage - penalty - objects[hit_id].defense(game);
+            let dealt = objects[hit_id].resisted_damage(damage, DamageType::Physical, game);
+            if dealt > 0 {
+                game.messages.add(
+                    format!(
+                        "Your {} hits {} for {} hit points.{}",
+                        shot.name,
+                        objects[hit_id].name,
+                        dealt,
+                        objects[hit_id].effectiveness(damage, DamageType::Physical, game)
+                    ),
+                    WHITE,
+                    Category::Combat,
+                );
+                if let Some(xp) = objects[hit_id].take_damage(damage, DamageType::Physical, game) {
+                    objects[PLAYER].fighter.as_mut().unwrap().xp += xp;
+                }
+            } else {
+                game.messages.add(
+                    format!(
+                        "Your {} glances off {}.{}",
+                        shot.name,
+                        objects[hit_id].name,
+                        objects[hit_id].effectiveness(damage, DamageType::Physical, game)
+                    ),
+                    WHITE,
+                    Category::Combat,
+                );
+            }
+        }
+        None => {
+            game.messages
+                .add(format!("Your {} hits the wall.", shot.name), WHITE, Category::Combat);
+        }
+    }
+
+    if rand::thread_rng().gen_range(0, 100) < AMMO_BREAK_CHANCE {
+        game.messages
+            .add(format!("The {} breaks.", shot.name), LIGHT_GREY, Category::Loot);
+    } else {
+        shot.set_pos(landing.0, landing.1);
+        shot.always_visible = true;
+        place_on_floor(shot, objects);
+    }
+    true
+}
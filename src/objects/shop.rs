@@ -0,0 +1,185 @@
+use crate::objects::{
+    equipment::is_stuck,
+    player::{add_to_inventory, pick_item_up, place_on_floor},
+    *,
+};
+use crate::render::menus::{inventory_menu, menu};
+use crate::render::messages::Messages;
+
+pub fn trade(tcod: &mut Tcod, game: &mut Game, objects: &mut Vec<Object>) {
+    loop {
+        let header = format!("\"Welcome, traveller!\" You have {} gold.\n", game.gold);
+        match menu(&header, &["Buy", "Sell"], INVENTORY_WIDTH, &mut tcod.root) {
+            Some(0) => buy(tcod, game, objects),
+            Some(1) => sell(tcod, game, objects),
+            _ => return,
+        }
+    }
+}
+
+fn buy(tcod: &mut Tcod, game: &mut Game, objects: &mut Vec<Object>) {
+    // goods the player already picked up come first and are paid for where they are
+    let carried: Vec<usize> = (0..game.inventory.len())
+        .filter(|&id| game.inventory[id].price.is_some())
+        .collect();
+    let stock: Vec<usize> = (0..objects.len()).filter(|&id| objects[id].price.is_some()).collect();
+    if carried.is_empty() && stock.is_empty() {
+        game.messages
+            .add("\"Sorry, I'm sold out.\"", LIGHT_GREY, Category::System);
+        return;
+    }
+    let mut options: Vec<_> = carried
+        .iter()
+        .map(|&id| {
+            let item = &game.inventory[id];
+            format!("{} - {} gold (carried)", item.stack_name(game), item.price.unwrap())
+        })
+        .collect();
+    options.extend(
+        stock
+            .iter()
+            .map(|&id| format!("{} - {} gold", objects[id].stack_name(game), objects[id].price.unwrap())),
+    );
+    let choice = menu(
+        "Choose an item to buy, or any other key to cancel.\n",
+        &options,
+        INVENTORY_WIDTH,
+        &mut tcod.root,
+    );
+    if let Some(choice) = choice {
+        let price = match carried.get(choice) {
+            Some(&id) => game.inventory[id].price.unwrap(),
+            None => objects[stock[choice - carried.len()]].price.unwrap(),
+        };
+        if price > game.gold {
+            game.messages.add("You cannot afford that.", RED, Category::System);
+            return;
+        }
+        if let Some(&id) = carried.get(choice) {
+            // taken out and put back so it can join its stack or be worn
+            let mut item = game.inventory.remove(id);
+            item.price = None;
+            add_to_inventory(item, game);
+        } else {
+            let id = stock[choice - carried.len()];
+            objects[id].price = None;
+            if !pick_item_up(id, game, objects) {
+                // no room to carry it, so it stays on the shelf
+                objects[id].price = Some(price);
+                return;
+            }
+        }
+        game.gold -= price;
+        if let Some(purse) = purse(objects) {
+            purse.quantity += price;
+        }
+        game.messages
+            .add(format!("You pay {} gold.", price), GOLD, Category::Loot);
+    }
+}
+
+fn purse(objects: &mut [Object]) -> Option<&mut Object> {
+    objects
+        .iter_mut()
+        .filter(|object| object.ai == Some(AI::Shopkeeper))
+        .flat_map(|shopkeeper| shopkeeper.inventory.iter_mut())
+        .find(|object| object.item == Some(Item::Gold))
+}
+
+fn sell(tcod: &mut Tcod, game: &mut Game, objects: &mut Vec<Object>) {
+    let shopkeeper = match objects.iter().position(|object| object.ai == Some(AI::Shopkeeper)) {
+        Some(shopkeeper) => shopkeeper,
+        None => return,
+    };
+    let inventory_index = inventory_menu(
+        game,
+        "Press the key next to an item to sell it, or any other to cancel.\n",
+        &mut tcod.root,
+    );
+    if let Some(inventory_index) = inventory_index {
+        if is_stuck(&game.inventory[inventory_index], &mut game.messages)
+            || is_unpaid(&game.inventory[inventory_index], &mut game.messages)
+        {
+            return;
+        }
+        if !game.inventory[inventory_index].inventory.is_empty() {
+            game.messages
+                .add("\"Empty it first, please.\"", LIGHT_GREY, Category::System);
+            return;
+        }
+        // shopkeepers buy one at a time, for half of what they ask
+        let price = game.inventory[inventory_index].value() / game.inventory[inventory_index].quantity / 2;
+        let purse = objects[shopkeeper]
+            .inventory
+            .iter()
+            .position(|object| object.item == Some(Item::Gold));
+        let purse = match purse {
+            Some(purse) if objects[shopkeeper].inventory[purse].quantity >= price => purse,
+            _ => {
+                game.messages
+                    .add("\"I can't afford that, sorry.\"", LIGHT_GREY, Category::System);
+                return;
+            }
+        };
+        let mut item = if game.inventory[inventory_index].quantity > 1 {
+            game.inventory[inventory_index].split(1)
+        } else {
+            game.inventory.remove(inventory_index)
+        };
+        if item.equipment.is_some() {
+            item.dequip(&mut game.messages);
+        }
+        objects[shopkeeper].inventory[purse].quantity -= price;
+        game.gold += price;
+        game.messages.add(
+            format!("You sell {} for {} gold.", item.description(game), price),
+            GOLD,
+            Category::Loot,
+        );
+        // back on the shelf at the full price
+        item.set_pos(objects[PLAYER].x, objects[PLAYER].y);
+        item.price = Some(item.value());
+        item.always_visible = true;
+        place_on_floor(item, objects);
+    }
+}
+
+pub fn anger_shopkeeper(game: &mut Game, objects: &mut [Object]) {
+    if let Some(shopkeeper) = objects.iter_mut().find(|object| object.ai == Some(AI::Shopkeeper)) {
+        shopkeeper.ai = Some(AI::Basic);
+        game.messages.add(
+            format!("The {} shouts: \"Thief!\"", shopkeeper.name),
+            RED,
+            Category::Combat,
+        );
+    }
+}
+
+// unpaid goods can be carried around the shop, but not used, thrown, stowed or sold
+pub fn is_unpaid(item: &Object, messages: &mut Messages) -> bool {
+    if item.price.is_some() {
+        messages.add(
+            format!("You have to pay for the {} first.", item.name),
+            RED,
+            Category::System,
+        );
+    }
+    item.price.is_some()
+}
+
+// walking out of the shop with unpaid goods is stealing them
+pub fn check_unpaid(game: &mut Game, objects: &mut [Object]) {
+    let (x, y) = objects[PLAYER].pos();
+    if !game.map[x as usize][y as usize].shop {
+        steal_unpaid(game, objects);
+    }
+}
+
+pub fn steal_unpaid(game: &mut Game, objects: &mut [Object]) {
+    if game.inventory.iter().any(|item| item.price.is_some()) {
+        for item in game.inventory.iter_mut() {
+            item.price = None;
+        }
+        anger_shopkeeper(game, objects);
+    }
+}
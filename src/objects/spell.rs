@@ -0,0 +1,126 @@
+use std::cmp;
+
+use serde::{Deserialize, Serialize};
+
+use crate::objects::{item::*, *};
+use crate::render::menus::menu;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Spell {
+    Heal,
+    Lightning,
+    Confuse,
+    Fireball,
+}
+
+// spells share the scroll effects, which ignore the inventory slot they were read from
+const NO_ITEM: usize = usize::MAX;
+
+impl Spell {
+    pub const ALL: [Spell; 4] = [Spell::Heal, Spell::Lightning, Spell::Confuse, Spell::Fireball];
+
+    pub fn cost(self) -> i32 {
+        match self {
+            Spell::Heal => 8,
+            Spell::Lightning => 5,
+            Spell::Confuse => 4,
+            Spell::Fireball => 10,
+        }
+    }
+
+    fn effect(self) -> fn(usize, &mut Tcod, &mut Game, &mut [Object]) -> UseResult {
+        match self {
+            Spell::Heal => cast_heal,
+            Spell::Lightning => cast_lightning,
+            Spell::Confuse => cast_confuse,
+            Spell::Fireball => cast_fireball,
+        }
+    }
+}
+
+impl std::fmt::Display for Spell {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            Spell::Heal => write!(f, "heal"),
+            Spell::Lightning => write!(f, "lightning bolt"),
+            Spell::Confuse => write!(f, "confusion"),
+            Spell::Fireball => write!(f, "fireball"),
+        }
+    }
+}
+
+pub fn learn_spell(spell: Spell, game: &mut Game) -> bool {
+    if game.spells.contains(&spell) {
+        game.messages.add(
+            format!("You already know the {} spell.", spell),
+            WHITE,
+            Category::System,
+        );
+        return false;
+    }
+    game.spells.push(spell);
+    game.messages
+        .add(format!("You learn the {} spell!", spell), LIGHT_BLUE, Category::System);
+    true
+}
+
+pub fn cast_learn(inventory_id: usize, _tcod: &mut Tcod, game: &mut Game, _objects: &mut [Object]) -> UseResult {
+    let spell = match game.inventory[inventory_id].item {
+        Some(Item::BookHeal) => Spell::Heal,
+        Some(Item::BookLightning) => Spell::Lightning,
+        Some(Item::BookConfuse) => Spell::Confuse,
+        Some(Item::BookFireball) => Spell::Fireball,
+        _ => return UseResult::Cancelled,
+    };
+    if learn_spell(spell, game) {
+        UseResult::UsedUp
+    } else {
+        UseResult::Cancelled
+    }
+}
+
+pub fn cast_spell(tcod: &mut Tcod, game: &mut Game, objects: &mut [Object]) -> bool {
+    if game.spells.is_empty() {
+        game.messages.add("You don't know any spells.", RED, Category::System);
+        return false;
+    }
+    let options: Vec<_> = game
+        .spells
+        .iter()
+        .map(|spell| format!("{} ({} mana)", spell, spell.cost()))
+        .collect();
+    let choice = menu(
+        &format!(
+            "Choose a spell to cast ({} mana left), or any other key to cancel.\n",
+            game.mana
+        ),
+        &options,
+        INVENTORY_WIDTH,
+        &mut tcod.root,
+    );
+    let spell = match choice {
+        Some(choice) => game.spells[choice],
+        None => return false,
+    };
+    if spell.cost() > game.mana {
+        game.messages.add(
+            format!("You don't have enough mana to cast {}.", spell),
+            RED,
+            Category::System,
+        );
+        return false;
+    }
+    match spell.effect()(NO_ITEM, tcod, game, objects) {
+        UseResult::Cancelled => false,
+        _ => {
+            game.mana -= spell.cost();
+            true
+        }
+    }
+}
+
+pub fn regenerate_mana(game: &mut Game) {
+    if rand::thread_rng().gen_range(0, MANA_REGEN_INTERVAL) == 0 {
+        game.mana = cmp::min(game.mana + 1, game.max_mana);
+    }
+}
@@ -0,0 +1,85 @@
+use crate::keymap::Action;
+use crate::render::{messages::Category, *};
+
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![];
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.len() + 1 + word.len() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    lines.push(line);
+    lines
+}
+
+pub fn message_log(tcod: &mut Tcod, game: &Game) {
+    use tcod::input::KeyCode::*;
+    let page = SCREEN_HEIGHT - 2;
+    let mut filter: Option<Category> = None;
+    // lines scrolled back from the newest message
+    let mut scroll = 0;
+    loop {
+        let lines: Vec<(String, Color)> = game
+            .messages
+            .iter()
+            .filter(|&(_, _, category)| filter.is_none() || filter == Some(category))
+            .flat_map(|(message, color, _)| {
+                wrap(&message, (SCREEN_WIDTH - 2) as usize)
+                    .into_iter()
+                    .map(move |line| (line, color))
+            })
+            .collect();
+        let max_scroll = (lines.len() as i32 - page).max(0);
+        scroll = scroll.clamp(0, max_scroll);
+        let end = lines.len() as i32 - scroll;
+        let start = (end - page).max(0);
+
+        tcod.root.set_default_background(BLACK);
+        tcod.root.clear();
+        tcod.root.set_default_foreground(LIGHT_GREY);
+        let shown = filter.map_or("all".into(), |category| category.to_string());
+        tcod.root.print_ex(
+            1,
+            0,
+            BackgroundFlag::None,
+            TextAlignment::Left,
+            format!("Messages: {} (Tab to filter, Escape to close)", shown),
+        );
+        for (row, (line, color)) in lines[start as usize..end as usize].iter().enumerate() {
+            tcod.root.set_default_foreground(*color);
+            tcod.root
+                .print_ex(1, 2 + row as i32, BackgroundFlag::None, TextAlignment::Left, line);
+        }
+        tcod.root.flush();
+
+        let key = tcod.root.wait_for_keypress(true);
+        match key.code {
+            Escape => return,
+            Tab => {
+                filter = match filter {
+                    None => Some(Category::ALL[0]),
+                    Some(category) => Category::ALL
+                        .iter()
+                        .position(|&other| other == category)
+                        .and_then(|index| Category::ALL.get(index + 1).cloned()),
+                };
+                scroll = 0;
+            }
+            PageUp => scroll += page,
+            PageDown => scroll -= page,
+            Home => scroll = max_scroll,
+            End => scroll = 0,
+            _ if tcod.keymap.is_pressed(key, Action::MessageLog) => return,
+            _ => {
+                if let Some((_, dy)) = tcod.keymap.direction(key) {
+                    scroll -= dy;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use tcod::input::Key;
+
+use crate::keymap::Action;
+use crate::objects::{ai::AI, damage::DamageType, equipment::Equipment};
+use crate::render::*;
+
+pub fn move_cursor((x, y): (i32, i32), (dx, dy): (i32, i32)) -> (i32, i32) {
+    ((x + dx).clamp(0, MAP_WIDTH - 1), (y + dy).clamp(0, MAP_HEIGHT - 1))
+}
+
+pub fn visible_things(tcod: &Tcod, objects: &[Object]) -> Vec<(i32, i32)> {
+    let mut things: Vec<&Object> = objects
+        .iter()
+        .skip(PLAYER + 1)
+        .filter(|object| (object.fighter.is_some() || object.item.is_some()) && tcod.fov.is_in_fov(object.x, object.y))
+        .collect();
+    let (x, y) = objects[PLAYER].pos();
+    // ties broken by position so that everything on one tile ends up together
+    things.sort_by(|a, b| {
+        a.distance(x, y)
+            .partial_cmp(&b.distance(x, y))
+            .unwrap()
+            .then_with(|| a.pos().cmp(&b.pos()))
+    });
+    let mut positions: Vec<(i32, i32)> = things.iter().map(|object| object.pos()).collect();
+    positions.dedup();
+    positions
+}
+
+pub fn look(tcod: &mut Tcod, game: &mut Game, objects: &[Object]) {
+    use tcod::input::KeyCode::*;
+    let things = visible_things(tcod, objects);
+    let mut next_thing = 0;
+    let mut cursor = match things.first() {
+        Some(&pos) => {
+            next_thing = 1;
+            pos
+        }
+        None => objects[PLAYER].pos(),
+    };
+    loop {
+        render_all(tcod, game, objects, false);
+        tcod.root
+            .set_char_background(cursor.0, cursor.1, LIGHT_YELLOW, BackgroundFlag::Set);
+        let lines = describe(cursor, tcod, game, objects);
+        render_description(&lines, cursor, &mut tcod.root);
+        tcod.root.flush();
+
+        let key = tcod.root.wait_for_keypress(true);
+        match key {
+            Key { code: Escape, .. } => return,
+            _ if tcod.keymap.is_pressed(key, Action::Look) => return,
+            Key { code: Tab, .. } if !things.is_empty() => {
+                cursor = things[next_thing];
+                next_thing = (next_thing + 1) % things.len();
+            }
+            _ => {
+                if let Some(direction) = tcod.keymap.direction(key) {
+                    cursor = move_cursor(cursor, direction);
+                }
+            }
+        }
+    }
+}
+
+fn render_description(lines: &[(String, Color)], cursor: (i32, i32), root: &mut Root) {
+    let height: i32 = lines
+        .iter()
+        .map(|(line, _)| root.get_height_rect(0, 0, LOOK_WIDTH - 2, SCREEN_HEIGHT, line))
+        .sum::<i32>()
+        + 2;
+    let mut window = Offscreen::new(LOOK_WIDTH, height);
+    window.set_default_foreground(LIGHT_GREY);
+    window.print_frame(0, 0, LOOK_WIDTH, height, true, BackgroundFlag::Set, None::<&str>);
+    let mut y = 1;
+    for (line, color) in lines {
+        window.set_default_foreground(*color);
+        y += window.print_rect(1, y, LOOK_WIDTH - 2, 0, line);
+    }
+    // keep the panel out of the way of the cursor
+    let x = if cursor.0 < SCREEN_WIDTH / 2 {
+        SCREEN_WIDTH - LOOK_WIDTH
+    } else {
+        0
+    };
+    blit(&window, (0, 0), (LOOK_WIDTH, height), root, (x, 0), 1.0, 0.9);
+}
+
+pub fn describe((x, y): (i32, i32), tcod: &Tcod, game: &Game, objects: &[Object]) -> Vec<(String, Color)> {
+    let tile = game.map[x as usize][y as usize];
+    if !tile.explored {
+        return vec![("You haven't seen this place.".into(), LIGHT_GREY)];
+    }
+    let visible = tcod.fov.is_in_fov(x, y);
+    let mut lines = vec![];
+    for (id, object) in objects.iter().enumerate() {
+        if object.pos() != (x, y) || !(visible || object.always_visible) {
+            continue;
+        }
+        if id == PLAYER {
+            lines.push(("You.".into(), WHITE));
+        } else if object.fighter.is_some() {
+            lines.extend(describe_monster(object, game));
+        } else if object.item.is_some() {
+            lines.extend(describe_item(object, game));
+        } else {
+            lines.push((object.name.clone(), object.color));
+        }
+    }
+    if lines.is_empty() {
+        let name = if tile.blocked { "A wall." } else { "The floor." };
+        lines.push((name.into(), LIGHT_GREY));
+    }
+    lines
+}
+
+fn describe_monster(monster: &Object, game: &Game) -> Vec<(String, Color)> {
+    let fighter = monster.fighter.unwrap();
+    let health = health_state(fighter.hp, monster.max_hp(game));
+    let mut lines = vec![
+        (monster.name.clone(), monster.color),
+        (format!("It is {}.", health), LIGHT_RED),
+        (
+            format!("Attack {}, defense {}.", monster.power(game), monster.defense(game)),
+            WHITE,
+        ),
+    ];
+    for &damage_type in DamageType::ALL.iter() {
+        let resistance = monster.resistance(damage_type, game);
+        if resistance != 0 {
+            lines.push((format!("{} resistance {}%.", damage_type, resistance), LIGHT_BLUE));
+        }
+    }
+    match monster.ai {
+        Some(AI::Confused { num_turns, .. }) => {
+            lines.push((format!("It is confused for {} more turns.", num_turns), LIGHT_GREEN))
+        }
+        Some(AI::Shopkeeper) => lines.push(("It minds the shop.".into(), GOLD)),
+        _ => {}
+    }
+    lines
+}
+
+fn health_state(hp: i32, max_hp: i32) -> &'static str {
+    let fraction = hp as f32 / max_hp as f32;
+    if fraction >= 1.0 {
+        "unhurt"
+    } else if fraction > 0.7 {
+        "lightly wounded"
+    } else if fraction > 0.4 {
+        "wounded"
+    } else if fraction > 0.15 {
+        "badly wounded"
+    } else {
+        "almost dead"
+    }
+}
+
+fn describe_item(item: &Object, game: &Game) -> Vec<(String, Color)> {
+    let mut lines = vec![(item.stack_name(game), item.rarity().color())];
+    if let Some(price) = item.price {
+        lines.push((format!("For sale: {} gold.", price), GOLD));
+    }
+    if let Some(mut equipment) = item.equipment {
+        // an unknown enchantment stays hidden
+        if !equipment.known {
+            equipment.enchant(-equipment.enchantment);
+        }
+        lines.extend(describe_equipment(equipment));
+    }
+    if item.is_unidentified(game) {
+        lines.push(("You don't know its powers yet.".into(), LIGHT_GREY));
+    }
+    lines
+}
+
+fn describe_equipment(equipment: Equipment) -> Vec<(String, Color)> {
+    let mut lines = vec![(format!("Worn on the {}.", equipment.location()), WHITE)];
+    let bonuses = [
+        (equipment.power_bonus, "attack"),
+        (equipment.defense_bonus, "defense"),
+        (equipment.hp_bonus, "HP"),
+    ];
+    for &(bonus, name) in bonuses.iter() {
+        if bonus != 0 {
+            lines.push((format!("{:+} {}", bonus, name), LIGHT_GREEN));
+        }
+    }
+    for &damage_type in DamageType::ALL.iter() {
+        let resistance = equipment.resistances.get(damage_type);
+        if resistance != 0 {
+            lines.push((format!("{:+}% {} resistance", resistance, damage_type), LIGHT_BLUE));
+        }
+    }
+    if let Some(brand) = equipment.brand {
+        lines.push((format!("+{} {} damage", brand.damage, brand.kind), ORANGE));
+    }
+    if let Some(launcher) = equipment.launcher {
+        lines.push((
+            format!(
+                "Shoots {} for {} damage, range {}",
+                launcher.ammo, launcher.damage, launcher.range
+            ),
+            WHITE,
+        ));
+    }
+    lines
+}
@@ -1,71 +1,352 @@
+use std::cmp;
+use std::ops::Range;
+
+use tcod::input::{self, Event};
+
+use crate::objects::equipment::paper_doll;
 use crate::render::*;
 
 pub fn menu<T: AsRef<str>>(header: &str, options: &[T], width: i32, root: &mut Root) -> Option<usize> {
-    assert!(options.len() <= 26, "Cannot have a menu with more than 26 options.");
+    let options: Vec<_> = options.iter().map(|option| (option.as_ref(), WHITE)).collect();
+    colored_menu(header, &options, width, root)
+}
+
+pub fn colored_menu<T: AsRef<str>>(header: &str, options: &[(T, Color)], width: i32, root: &mut Root) -> Option<usize> {
+    let lines: Vec<_> = options
+        .iter()
+        .map(|(option, color)| MenuLine::Choice(option.as_ref(), *color))
+        .collect();
+    grouped_menu(header, &lines, width, root)
+}
+
+pub enum MenuLine<T> {
+    Heading(T),
+    Choice(T, Color),
+}
+
+// returns the index among the choices, headings not counted
+pub fn grouped_menu<T: AsRef<str>>(header: &str, lines: &[MenuLine<T>], width: i32, root: &mut Root) -> Option<usize> {
+    run_menu(header, lines, width, root, false).map(|chosen| chosen[0])
+}
+
+pub fn multi_select_menu<T: AsRef<str>>(
+    header: &str,
+    lines: &[MenuLine<T>],
+    width: i32,
+    root: &mut Root,
+) -> Vec<usize> {
+    run_menu(header, lines, width, root, true).unwrap_or_default()
+}
+
+// pages fit under the header and have a letter for every choice
+fn paginate<T>(lines: &[MenuLine<T>], page_height: usize) -> Vec<Range<usize>> {
+    let mut pages = vec![];
+    let mut start = 0;
+    let mut choices = 0;
+    for (index, line) in lines.iter().enumerate() {
+        let is_choice = matches!(line, MenuLine::Choice(..));
+        if index - start == page_height || (is_choice && choices == 26) {
+            // a heading moves to the next page along with the choices under it
+            let end = match lines[index - 1] {
+                MenuLine::Heading(_) if index - 1 > start => index - 1,
+                _ => index,
+            };
+            pages.push(start..end);
+            start = end;
+            choices = 0;
+        }
+        if is_choice {
+            choices += 1;
+        }
+    }
+    pages.push(start..lines.len());
+    pages
+}
+
+// with `multi` set, picking marks a choice and Enter confirms all marked ones
+fn run_menu<T: AsRef<str>>(
+    header: &str,
+    lines: &[MenuLine<T>],
+    width: i32,
+    root: &mut Root,
+    multi: bool,
+) -> Option<Vec<usize>> {
+    use tcod::input::KeyCode::*;
 
     let header_height = if header.is_empty() {
         0
     } else {
         root.get_height_rect(0, 0, width, SCREEN_HEIGHT, header)
     };
-    let height = options.len() as i32 + header_height;
-
-    let mut window = Offscreen::new(width, height);
-
-    window.set_default_foreground(WHITE);
-    window.print_rect_ex(0, 0, width, height, BackgroundFlag::None, TextAlignment::Left, header);
-
-    for (index, option_text) in options.iter().enumerate() {
-        let menu_letter = (b'a' + index as u8) as char;
-        let text = format!("({}) {}", menu_letter, option_text.as_ref());
-        window.print_ex(
-            0,
-            header_height + index as i32,
-            BackgroundFlag::None,
-            TextAlignment::Left,
-            text,
-        );
-    }
-
+    // index among the choices of every line that is one
+    let mut choice_count = 0;
+    let choice_ids: Vec<Option<usize>> = lines
+        .iter()
+        .map(|line| match line {
+            MenuLine::Choice(..) => {
+                choice_count += 1;
+                Some(choice_count - 1)
+            }
+            MenuLine::Heading(_) => None,
+        })
+        .collect();
+    let pages = paginate(lines, cmp::max(SCREEN_HEIGHT - header_height - 1, 1) as usize);
+    let footer = pages.len() > 1 || multi;
+    let rows = pages.iter().map(|page| page.len()).max().unwrap_or(0) as i32;
+    let height = header_height + rows + if footer { 1 } else { 0 };
     let x = SCREEN_WIDTH / 2 - width / 2;
-    let y = SCREEN_HEIGHT / 2 - width / 2;
-    blit(&window, (0, 0), (width, height), root, (x, y), 1.0, 0.7);
-
-    root.flush();
-    let key = root.wait_for_keypress(true);
-
-    if key.printable.is_alphabetic() {
-        let index = key.printable.to_ascii_lowercase() as usize - 'a' as usize;
-        if index < options.len() {
-            Some(index)
-        } else {
-            None
+    let y = SCREEN_HEIGHT / 2 - height / 2;
+
+    let first_choice = |page: &Range<usize>| page.clone().find(|&index| choice_ids[index].is_some());
+    let mut page = 0;
+    let mut selected = first_choice(&pages[0]);
+    let mut marked = vec![false; choice_count];
+    // forget keys pressed before the menu opened
+    while input::check_for_event(input::KEY_PRESS).is_some() {}
+    loop {
+        let mut window = Offscreen::new(width, height);
+        window.set_default_foreground(WHITE);
+        window.print_rect_ex(0, 0, width, height, BackgroundFlag::None, TextAlignment::Left, header);
+
+        let mut letter = b'a';
+        for (row, index) in pages[page].clone().enumerate() {
+            let row = header_height + row as i32;
+            match &lines[index] {
+                MenuLine::Heading(text) => {
+                    window.set_default_foreground(LIGHT_GREY);
+                    window.print_ex(0, row, BackgroundFlag::None, TextAlignment::Left, text.as_ref());
+                }
+                MenuLine::Choice(text, color) => {
+                    if selected == Some(index) {
+                        window.set_default_background(DARKER_GREY);
+                        window.rect(0, row, width, 1, false, BackgroundFlag::Set);
+                    }
+                    let mark = match (multi, choice_ids[index].map(|id| marked[id])) {
+                        (true, Some(true)) => "+ ",
+                        (true, _) => "- ",
+                        _ => "",
+                    };
+                    let text = format!("({}) {}{}", letter as char, mark, text.as_ref());
+                    window.set_default_foreground(*color);
+                    window.print_ex(0, row, BackgroundFlag::None, TextAlignment::Left, text);
+                    letter += 1;
+                }
+            }
+        }
+        if footer {
+            let hint = if multi { "Enter to confirm" } else { "" };
+            let paging = if pages.len() > 1 {
+                format!("Page {}/{}, PgUp/PgDn ", page + 1, pages.len())
+            } else {
+                String::new()
+            };
+            window.set_default_foreground(LIGHT_GREY);
+            window.print_ex(
+                0,
+                header_height + rows,
+                BackgroundFlag::None,
+                TextAlignment::Left,
+                format!("{}{}", paging, hint),
+            );
+        }
+
+        blit(&window, (0, 0), (width, height), root, (x, y), 1.0, 0.7);
+        root.flush();
+
+        // a choice was picked by letter, click or Enter
+        let mut picked = None;
+        match input::check_for_event(input::KEY_PRESS | input::MOUSE).map(|event| event.1) {
+            Some(Event::Mouse(mouse)) => {
+                if mouse.rbutton_pressed {
+                    return None;
+                }
+                let (column, row) = (mouse.cx as i32 - x, mouse.cy as i32 - y - header_height);
+                let hovered = if column >= 0 && column < width && row >= 0 && (row as usize) < pages[page].len() {
+                    Some(pages[page].start + row as usize).filter(|&index| choice_ids[index].is_some())
+                } else {
+                    None
+                };
+                if hovered.is_some() {
+                    selected = hovered;
+                    if mouse.lbutton_pressed {
+                        picked = selected;
+                    }
+                }
+            }
+            Some(Event::Key(key)) => match key.code {
+                // the typed text and modifiers come as events of their own
+                Text | Shift | Control | Alt => {}
+                Up => {
+                    if let Some(previous) = (0..selected.unwrap_or(0)).rev().find(|&i| choice_ids[i].is_some()) {
+                        selected = Some(previous);
+                    }
+                }
+                Down => {
+                    let from = selected.map_or(0, |index| index + 1);
+                    if let Some(next) = (from..lines.len()).find(|&i| choice_ids[i].is_some()) {
+                        selected = Some(next);
+                    }
+                }
+                PageUp if page > 0 => {
+                    page -= 1;
+                    selected = first_choice(&pages[page]);
+                }
+                PageDown if page + 1 < pages.len() => {
+                    page += 1;
+                    selected = first_choice(&pages[page]);
+                }
+                PageUp | PageDown => {}
+                Enter | NumPadEnter if multi => {
+                    let mut chosen: Vec<usize> = (0..choice_count).filter(|&id| marked[id]).collect();
+                    if chosen.is_empty() {
+                        chosen.extend(selected.and_then(|index| choice_ids[index]));
+                    }
+                    return Some(chosen);
+                }
+                Enter | NumPadEnter if selected.is_none() => return None,
+                Enter | NumPadEnter => picked = selected,
+                Spacebar if multi => picked = selected,
+                _ if key.printable.is_alphabetic() => {
+                    let letter = key.printable.to_ascii_lowercase() as usize - 'a' as usize;
+                    // a letter without an option cancels, like any other key
+                    let index = pages[page]
+                        .clone()
+                        .filter(|&index| choice_ids[index].is_some())
+                        .nth(letter)?;
+                    picked = Some(index);
+                }
+                _ => return None,
+            },
+            None => {}
+        }
+        // keep the page on the selection after moving past its edge
+        if let Some(index) = selected {
+            page = pages.iter().position(|range| range.contains(&index)).unwrap_or(page);
+        }
+        if let Some(id) = picked.and_then(|index| choice_ids[index]) {
+            if multi {
+                marked[id] = !marked[id];
+                selected = picked;
+            } else {
+                return Some(vec![id]);
+            }
         }
-    } else {
-        None
     }
 }
 
-pub fn inventory_menu(inventory: &[Object], header: &str, root: &mut Root) -> Option<usize> {
-    let options = if inventory.len() == 0 {
-        vec!["Invenctory is empty.".into()]
-    } else {
-        inventory
-            .iter()
-            .map(|item| match item.equipment {
-                Some(equipment) if equipment.equipped => {
-                    format!("{} (on {})", item.name, equipment.slot)
+pub fn number_prompt(header: &str, max: u32, root: &mut Root) -> Option<u32> {
+    use tcod::input::KeyCode::*;
+
+    let width = INVENTORY_WIDTH;
+    let mut text = max.to_string();
+    // the default is replaced by the first digit typed rather than added to
+    let mut prefilled = true;
+    loop {
+        let prompt = format!("{} (1-{}): {}_", header, max, text);
+        let height = root.get_height_rect(0, 0, width, SCREEN_HEIGHT, &prompt);
+
+        let mut window = Offscreen::new(width, height);
+        window.set_default_foreground(WHITE);
+        window.print_rect_ex(0, 0, width, height, BackgroundFlag::None, TextAlignment::Left, &prompt);
+
+        let x = SCREEN_WIDTH / 2 - width / 2;
+        let y = SCREEN_HEIGHT / 2 - height / 2;
+        blit(&window, (0, 0), (width, height), root, (x, y), 1.0, 0.7);
+        root.flush();
+
+        let key = root.wait_for_keypress(true);
+        match key.code {
+            Enter | NumPadEnter => return text.parse().ok().filter(|&count| count >= 1 && count <= max),
+            Escape => return None,
+            Backspace => {
+                text.pop();
+                prefilled = false;
+            }
+            _ if key.printable.is_ascii_digit() && (prefilled || text.len() < 9) => {
+                if prefilled {
+                    text.clear();
+                    prefilled = false;
                 }
-                _ => item.name.clone(),
-            })
-            .collect()
-    };
+                text.push(key.printable);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn inventory_lines(game: &Game) -> (Vec<MenuLine<String>>, Vec<usize>) {
+    let inventory = &game.inventory;
+    if inventory.is_empty() {
+        return (vec![MenuLine::Choice("Invenctory is empty.".into(), WHITE)], vec![]);
+    }
+    let mut order: Vec<usize> = (0..inventory.len()).collect();
+    order.sort_by_key(|&index| inventory[index].item.map(|item| item.category()));
+    let mut lines = vec![];
+    let mut category = None;
+    for &index in &order {
+        let item = &inventory[index];
+        let item_category = item.item.map(|item| item.category());
+        if item_category != category {
+            category = item_category;
+            if let Some(category) = category {
+                lines.push(MenuLine::Heading(category.to_string()));
+            }
+        }
+        let name = match (item.equipment, item.price) {
+            (_, Some(price)) => format!("{} (unpaid, {} gold)", item.stack_name(game), price),
+            (Some(equipment), None) if equipment.equipped => {
+                format!("{} (on {})", item.stack_name(game), equipment.location())
+            }
+            _ => item.stack_name(game),
+        };
+        lines.push(MenuLine::Choice(name, item.rarity().color()));
+    }
+    (lines, order)
+}
+
+pub fn inventory_menu(game: &Game, header: &str, root: &mut Root) -> Option<usize> {
+    let (lines, order) = inventory_lines(game);
+    let header = format!("Equipped:\n{}\n\n{}", paper_doll(&game.inventory), header);
+    grouped_menu(&header, &lines, INVENTORY_WIDTH, root).and_then(|choice| order.get(choice).cloned())
+}
+
+pub fn inventory_multi_menu(game: &Game, header: &str, root: &mut Root) -> Vec<usize> {
+    let (lines, order) = inventory_lines(game);
+    multi_select_menu(header, &lines, INVENTORY_WIDTH, root)
+        .into_iter()
+        .filter_map(|choice| order.get(choice).cloned())
+        .collect()
+}
 
-    let inventory_index = menu(header, &options, INVENTORY_WIDTH, root);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    if inventory.len() > 0 {
-        inventory_index
-    } else {
-        None
+    fn choices(count: usize) -> Vec<MenuLine<&'static str>> {
+        (0..count).map(|_| MenuLine::Choice("choice", WHITE)).collect()
+    }
+
+    #[test]
+    fn short_menus_fit_one_page() {
+        assert_eq!(paginate(&choices(5), 10), vec![0..5]);
+        assert_eq!(paginate(&choices(0), 10), vec![0..0]);
+    }
+
+    #[test]
+    fn long_menus_break_at_the_page_height() {
+        assert_eq!(paginate(&choices(25), 10), vec![0..10, 10..20, 20..25]);
+    }
+
+    #[test]
+    fn pages_hold_at_most_one_letter_per_choice() {
+        assert_eq!(paginate(&choices(30), 40), vec![0..26, 26..30]);
+    }
+
+    #[test]
+    fn headings_move_to_the_page_of_their_choices() {
+        let mut lines = choices(3);
+        lines.push(MenuLine::Heading("heading"));
+        lines.extend(choices(3));
+        assert_eq!(paginate(&lines, 4), vec![0..3, 3..7]);
     }
 }
@@ -1,21 +1,162 @@
-use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Deserializer, Serialize};
 use tcod::colors::Color;
 
+use crate::constants::MAX_MESSAGES;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Category {
+    Combat,
+    Loot,
+    System,
+}
+
+impl Category {
+    pub const ALL: [Category; 3] = [Category::Combat, Category::Loot, Category::System];
+}
+
+impl std::fmt::Display for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            Category::Combat => write!(f, "combat"),
+            Category::Loot => write!(f, "loot"),
+            Category::System => write!(f, "system"),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Messages {
-    messages: Vec<(String, Color)>,
+    #[serde(deserialize_with = "load_messages")]
+    messages: VecDeque<Entry>,
+}
+
+// a message, its colour and category, and how many times in a row it was logged
+type Entry = (String, Color, Category, u32);
+
+// older saves have neither category nor count
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SavedMessage {
+    Counted(String, Color, Category, u32),
+    Single(String, Color),
+}
+
+// an uncapped old log keeps only its newest messages
+fn load_messages<'de, D: Deserializer<'de>>(deserializer: D) -> Result<VecDeque<Entry>, D::Error> {
+    let saved = Vec::<SavedMessage>::deserialize(deserializer)?;
+    let skip = saved.len().saturating_sub(MAX_MESSAGES);
+    Ok(saved
+        .into_iter()
+        .skip(skip)
+        .map(|message| match message {
+            SavedMessage::Counted(text, color, category, count) => (text, color, category, count),
+            SavedMessage::Single(text, color) => (text, color, Category::System, 1),
+        })
+        .collect())
 }
 
 impl Messages {
     pub fn new() -> Self {
-        Self { messages: vec![] }
+        Self {
+            messages: VecDeque::new(),
+        }
+    }
+
+    pub fn add<T: Into<String>>(&mut self, message: T, color: Color, category: Category) {
+        let message = message.into();
+        if let Some(last) = self.messages.back_mut() {
+            if last.0 == message && last.1 == color && last.2 == category {
+                last.3 += 1;
+                return;
+            }
+        }
+        self.messages.push_back((message, color, category, 1));
+        if self.messages.len() > MAX_MESSAGES {
+            self.messages.pop_front();
+        }
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (String, Color, Category)> + '_ {
+        self.messages
+            .iter()
+            .map(|(message, color, category, count)| match count {
+                1 => (message.clone(), *color, *category),
+                _ => (format!("{} x{}", message, count), *color, *category),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tcod::colors::{RED, WHITE};
+
+    fn texts(messages: &Messages) -> Vec<String> {
+        messages.iter().map(|(text, _, _)| text).collect()
+    }
+
+    #[test]
+    fn repeats_in_a_row_are_counted() {
+        let mut messages = Messages::new();
+        messages.add("Orc attacks you.", RED, Category::Combat);
+        messages.add("Orc attacks you.", RED, Category::Combat);
+        messages.add("Orc attacks you.", RED, Category::Combat);
+        assert_eq!(texts(&messages), vec!["Orc attacks you. x3"]);
+    }
+
+    #[test]
+    fn repeats_with_something_in_between_are_kept_apart() {
+        let mut messages = Messages::new();
+        messages.add("Orc attacks you.", RED, Category::Combat);
+        messages.add("You picked up a sword!", WHITE, Category::Loot);
+        messages.add("Orc attacks you.", RED, Category::Combat);
+        assert_eq!(
+            texts(&messages),
+            vec!["Orc attacks you.", "You picked up a sword!", "Orc attacks you."]
+        );
+    }
+
+    #[test]
+    fn same_text_in_another_category_is_not_a_repeat() {
+        let mut messages = Messages::new();
+        messages.add("Hello.", WHITE, Category::Combat);
+        messages.add("Hello.", WHITE, Category::System);
+        let categories: Vec<_> = messages.iter().map(|(_, _, category)| category).collect();
+        assert_eq!(categories, vec![Category::Combat, Category::System]);
+    }
+
+    #[test]
+    fn the_oldest_messages_are_dropped_past_the_cap() {
+        let mut messages = Messages::new();
+        for i in 0..MAX_MESSAGES + 5 {
+            messages.add(format!("Message {}.", i), WHITE, Category::System);
+        }
+        let texts = texts(&messages);
+        assert_eq!(texts.len(), MAX_MESSAGES);
+        assert_eq!(texts[0], "Message 5.");
+        assert_eq!(texts[MAX_MESSAGES - 1], format!("Message {}.", MAX_MESSAGES + 4));
     }
 
-    pub fn add<T: Into<String>>(&mut self, message: T, color: Color) {
-        self.messages.push((message.into(), color));
+    #[test]
+    fn old_saves_load_as_single_system_messages_within_the_cap() {
+        let old: Vec<(String, Color)> = (0..MAX_MESSAGES + 2).map(|i| (format!("Old {}.", i), WHITE)).collect();
+        let json = serde_json::to_string(&serde_json::json!({ "messages": old })).unwrap();
+        let messages: Messages = serde_json::from_str(&json).unwrap();
+        let loaded: Vec<_> = messages.iter().collect();
+        assert_eq!(loaded.len(), MAX_MESSAGES);
+        assert_eq!(loaded[0].0, "Old 2.");
+        assert!(loaded.iter().all(|(_, _, category)| *category == Category::System));
     }
 
-    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &(String, Color)> {
-        self.messages.iter()
+    #[test]
+    fn counts_survive_a_save() {
+        let mut messages = Messages::new();
+        messages.add("Orc attacks you.", RED, Category::Combat);
+        messages.add("Orc attacks you.", RED, Category::Combat);
+        let json = serde_json::to_string(&messages).unwrap();
+        let loaded: Messages = serde_json::from_str(&json).unwrap();
+        assert_eq!(texts(&loaded), vec!["Orc attacks you. x2"]);
     }
 }
@@ -7,8 +7,11 @@ use serde::{Deserialize, Serialize};
 
 use crate::constants::*;
 use crate::map_generation::make_map::make_map;
-use crate::objects::{game::*, object::*, *};
+use crate::objects::{game::*, hunger::Hunger, object::*, shop::steal_unpaid, *};
+use messages::Category;
 
+pub mod history;
+pub mod look;
 pub mod menus;
 pub mod messages;
 
@@ -17,6 +20,9 @@ pub struct Tile {
     pub blocked: bool,
     pub block_sight: bool,
     pub explored: bool,
+    // inside a shop, where unpaid goods may be carried around
+    #[serde(default)]
+    pub shop: bool,
 }
 
 impl Tile {
@@ -25,6 +31,7 @@ impl Tile {
             blocked: false,
             block_sight: false,
             explored: false,
+            shop: false,
         }
     }
 
@@ -33,6 +40,7 @@ impl Tile {
             blocked: true,
             block_sight: true,
             explored: false,
+            shop: false,
         }
     }
 }
@@ -47,17 +55,23 @@ pub fn is_blocked(x: i32, y: i32, map: &Map, objects: &[Object]) -> bool {
 }
 
 pub fn next_level(tcod: &mut Tcod, game: &mut Game, objects: &mut Vec<Object>) {
-    game.messages
-        .add("You take a moment to rest, and recover your strength.", VIOLET);
+    // taking unpaid goods downstairs leaves the shop too
+    steal_unpaid(game, objects);
+    game.messages.add(
+        "You take a moment to rest, and recover your strength.",
+        VIOLET,
+        Category::System,
+    );
     let heal_hp = objects[PLAYER].max_hp(game) / 2;
     objects[PLAYER].heal(heal_hp, game);
 
     game.messages.add(
         "After a rare moment of peace, you descend deeper into the heart of the dungeon..",
         RED,
+        Category::System,
     );
     game.dungeon_level += 1;
-    game.map = make_map(objects, game.dungeon_level);
+    game.map = make_map(objects, game.dungeon_level, &tcod.affixes);
     initialise_fov(tcod, &game.map);
 }
 
@@ -92,14 +106,17 @@ fn render_bar(
     )
 }
 
-fn get_names_under_mouse(mouse: Mouse, objects: &[Object], fov_map: &FovMap) -> String {
+fn get_names_under_mouse(mouse: Mouse, game: &Game, objects: &[Object], fov_map: &FovMap) -> String {
     let (x, y) = (mouse.cx as i32, mouse.cy as i32);
 
     // create a list with the names of all objects at the mouse's coordinates and in FOV
     let names = objects
         .iter()
         .filter(|obj| obj.pos() == (x, y) && fov_map.is_in_fov(obj.x, obj.y))
-        .map(|obj| obj.name.clone())
+        .map(|obj| match obj.price {
+            Some(price) => format!("{} ({} gold)", obj.stack_name(game), price),
+            None => obj.stack_name(game),
+        })
         .collect::<Vec<_>>();
 
     names.join(", ") // join the names, separated by commas
@@ -152,7 +169,7 @@ pub fn render_all(tcod: &mut Tcod, game: &mut Game, objects: &[Object], fov_reco
         .collect();
     to_draw.sort_by(|o1, o2| o1.blocks.cmp(&o2.blocks));
     for object in &to_draw {
-        object.draw(&mut tcod.con);
+        object.draw(&mut tcod.con, game);
     }
     blit(
         &tcod.con,
@@ -169,8 +186,8 @@ pub fn render_all(tcod: &mut Tcod, game: &mut Game, objects: &[Object], fov_reco
     tcod.panel.clear();
 
     let mut y = MSG_HEIGHT as i32;
-    for &(ref msg, color) in game.messages.iter().rev() {
-        let msg_height = tcod.panel.get_height_rect(MSG_X, y, MSG_WIDTH, 0, msg);
+    for (msg, color, _) in game.messages.iter().rev() {
+        let msg_height = tcod.panel.get_height_rect(MSG_X, y, MSG_WIDTH, 0, &msg);
         y -= msg_height;
         if y < 0 {
             break;
@@ -192,6 +209,19 @@ pub fn render_all(tcod: &mut Tcod, game: &mut Game, objects: &[Object], fov_reco
         LIGHT_RED,
         DARKER_RED,
     );
+    if game.max_mana > 0 {
+        render_bar(
+            &mut tcod.panel,
+            1,
+            2,
+            BAR_WIDTH,
+            "MP",
+            game.mana,
+            game.max_mana,
+            LIGHT_BLUE,
+            DARKER_BLUE,
+        );
+    }
 
     tcod.panel.print_ex(
         1,
@@ -201,13 +231,29 @@ pub fn render_all(tcod: &mut Tcod, game: &mut Game, objects: &[Object], fov_reco
         format!("Dungeon level: {}", game.dungeon_level),
     );
 
+    let hunger = Hunger::from_satiation(game.satiation);
+    if hunger != Hunger::Satiated {
+        tcod.panel.set_default_foreground(hunger.color());
+        tcod.panel
+            .print_ex(1, 4, BackgroundFlag::None, TextAlignment::Left, hunger.to_string());
+    }
+
+    tcod.panel.set_default_foreground(GOLD);
+    tcod.panel.print_ex(
+        1,
+        5,
+        BackgroundFlag::None,
+        TextAlignment::Left,
+        format!("Gold: {}", game.gold),
+    );
+
     tcod.panel.set_default_foreground(LIGHT_GREY);
     tcod.panel.print_ex(
         1,
         0,
         BackgroundFlag::None,
         TextAlignment::Left,
-        get_names_under_mouse(tcod.mouse, objects, &tcod.fov),
+        get_names_under_mouse(tcod.mouse, game, objects, &tcod.fov),
     );
 
     blit(
@@ -4,49 +4,54 @@ use std::io::{Read, Write};
 
 use tcod::colors::*;
 use tcod::console::*;
-use tcod::input::{self, Event};
+use tcod::input::{self, Event, KeyCode};
 
+use crate::activity::*;
 use crate::constants::*;
 use crate::controls::*;
-use crate::map_generation::make_map::make_map;
-use crate::objects::{ai::*, equipment::*, fighter::*, game::*, item::*, object::*, player::*, Tcod};
-use crate::render::{menus::menu, messages::Messages, *};
-
-pub fn new_game(tcod: &mut Tcod) -> (Game, Vec<Object>) {
+use crate::map_generation::make_map::{create_item, make_map};
+use crate::objects::{
+    ai::*, class::Class, fighter::regenerate_health, game::*, hunger::tick_hunger, identification::random_appearances,
+    object::*, perk::tick_perks, player::*, shop::check_unpaid, spell::regenerate_mana, Tcod,
+};
+use crate::render::{
+    menus::menu,
+    messages::{Category, Messages},
+    *,
+};
+
+pub fn new_game(tcod: &mut Tcod, class: Class) -> (Game, Vec<Object>) {
     let mut player = Object::new(0, 0, '@', "player", WHITE, true);
     player.alive = true;
-    player.fighter = Some(Fighter {
-        hp: 100,
-        base_max_hp: 100,
-        base_defense: 1,
-        base_power: 2,
-        xp: 0,
-        on_death: DeathCallback::Player,
-    });
+    player.fighter = Some(class.fighter());
 
     let mut objects = vec![player];
 
     let mut game = Game {
-        map: make_map(&mut objects, 1),
+        map: make_map(&mut objects, 1, &tcod.affixes),
         messages: Messages::new(),
         inventory: vec![],
         dungeon_level: 1,
+        class,
+        dropped: vec![],
+        appearances: random_appearances(),
+        identified: vec![],
+        satiation: START_SATIATION,
+        gold: 0,
+        mana: class.max_mana(),
+        max_mana: class.max_mana(),
+        spells: class.spells(),
+        perks: vec![],
+        perk_points: 0,
+        activity: None,
     };
 
-    let mut dagger = Object::new(0, 0, '-', "dagger", SKY, false);
-    dagger.item = Some(Item::Sword);
-    dagger.equipment = Some(Equipment {
-        equipped: true,
-        slot: Slot::LeftHand,
-        hp_bonus: 0,
-        defense_bonus: 0,
-        power_bonus: 2,
-    });
-
-    game.inventory.push(dagger);
+    for item in class.kit() {
+        add_to_inventory(create_item(item, 0, 0), &mut game);
+    }
     initialise_fov(tcod, &game.map);
 
-    game.messages.add("Yo, welcome!", RED);
+    game.messages.add("Yo, welcome!", RED, Category::System);
 
     (game, objects)
 }
@@ -56,6 +61,8 @@ pub fn play_game(tcod: &mut Tcod, game: &mut Game, objects: &mut Vec<Object>) {
 
     while !tcod.root.window_closed() {
         tcod.con.clear();
+        // a click only counts on the frame it happens, not every frame until the mouse moves
+        tcod.mouse.lbutton_pressed = false;
         match input::check_for_event(input::MOUSE | input::KEY_PRESS) {
             Some((_, Event::Mouse(m))) => tcod.mouse = m,
             Some((_, Event::Key(k))) => tcod.key = k,
@@ -69,19 +76,38 @@ pub fn play_game(tcod: &mut Tcod, game: &mut Game, objects: &mut Vec<Object>) {
         level_up(tcod, game, objects);
 
         previous_player_position = objects[PLAYER].pos();
-        let player_action = handle_keys(tcod, game, objects);
+        let modifier = matches!(tcod.key.code, KeyCode::Shift | KeyCode::Control | KeyCode::Alt);
+        if game.activity.is_some() && tcod.key.code != KeyCode::NoKey && !modifier {
+            // any key takes control back, except a modifier still held from starting a run
+            stop_activity(game);
+            tcod.key = Default::default();
+        }
+        let player_action = if game.activity.is_some() {
+            continue_activity(tcod, game, objects)
+        } else {
+            handle_keys(tcod, game, objects)
+        };
         if player_action == PlayerAction::Exit {
             save_game(game, objects).unwrap();
             break;
         }
 
         if objects[PLAYER].alive && player_action == PlayerAction::TookTurn {
-            for id in 0..objects.len() {
-                if objects[id].ai.is_some() {
-                    ai_take_turn(id, &tcod, game, objects);
+            check_unpaid(game, objects);
+            // monsters keep acting while the player lies fainted
+            regenerate_health(game, objects);
+            regenerate_mana(game);
+            tick_perks(game, objects);
+            let turns = if tick_hunger(game, objects) { 1 + FAINT_TURNS } else { 1 };
+            for _ in 0..turns {
+                for id in 0..objects.len() {
+                    if objects[id].ai.is_some() {
+                        ai_take_turn(id, &tcod, game, objects);
+                    }
                 }
             }
         }
+        objects.append(&mut game.dropped);
     }
 }
 
@@ -134,8 +160,14 @@ pub fn main_menu(tcod: &mut Tcod) {
 
         match choice {
             Some(0) => {
-                let (mut game, mut objects) = new_game(tcod);
-                play_game(tcod, &mut game, &mut objects);
+                let classes: Vec<_> = Class::ALL
+                    .iter()
+                    .map(|class| format!("{}: {}", class, class.description()))
+                    .collect();
+                if let Some(choice) = menu("Choose your class:\n", &classes, CLASS_SCREEN_WIDTH, &mut tcod.root) {
+                    let (mut game, mut objects) = new_game(tcod, Class::ALL[choice]);
+                    play_game(tcod, &mut game, &mut objects);
+                }
             }
             Some(1) => {
                 match load_game() {